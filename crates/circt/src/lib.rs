@@ -5,11 +5,11 @@ use itertools::Itertools;
 use circt_sys as ffi;
 use mlir::{
     attr::{
-        ArrayAttr, DictionaryAttr, FlatSymbolRefAttr, FUNCTION_ARG_DICT_ATTR_NAME,
+        ArrayAttr, DictionaryAttr, FlatSymbolRefAttr, StringAttr, FUNCTION_ARG_DICT_ATTR_NAME,
         FUNCTION_RESULT_DICT_ATTR_NAME,
     },
-    Attribute, Identifier, Location, NamedAttribute, Operation, OperationState, StringRef,
-    SymbolTable, Type,
+    Attribute, Context, Error, Identifier, Location, NamedAttribute, Operation, OperationState,
+    StringRef, SymbolTable, Type,
 };
 
 /// Defines an extension trait on a type with trait methods of the form `fn(&self) -> bool`.
@@ -43,11 +43,205 @@ pub mod hw {
 
     use self::ty::TypeExt;
 
+    pub mod attr {
+        use super::*;
+
+        mlir::attr_types! {
+            pub struct InnerRefAttr = ffi::hwAttrIsAInnerRefAttr;
+            pub struct InnerSymAttr = ffi::hwAttrIsAInnerSymAttr;
+            pub struct ParamDeclAttr = ffi::hwAttrIsAParamDeclAttr;
+            pub struct ParamDeclRefAttr = ffi::hwAttrIsAParamDeclRefAttr;
+            pub struct ParamVerbatimAttr = ffi::hwAttrIsAParamVerbatimAttr;
+        }
+
+        fn null_handle(kind: &'static str) -> Error {
+            Error::NullHandle {
+                kind,
+                diagnostics: Vec::new(),
+            }
+        }
+
+        fn string_attr<'cx>(raw: ffi::MlirAttribute) -> StringAttr<'cx> {
+            unsafe { Attribute::from_raw(raw.into()) }
+                .and_then(|attr| attr.downcast().ok())
+                .expect("unexpected MLIR error: attribute should be a StringAttr")
+        }
+
+        impl<'cx> InnerRefAttr<'cx> {
+            /// Creates a reference to the inner symbol `name` within the module `module`.
+            pub fn new(module: StringAttr<'cx>, name: StringAttr<'cx>) -> InnerRefAttr<'cx> {
+                InnerRefAttr::try_new(module, name)
+                    .expect("unexpected MLIR error: InnerRefAttr should be non-null")
+            }
+
+            /// Creates a reference to the inner symbol `name` within the module `module`.
+            pub fn try_new(
+                module: StringAttr<'cx>,
+                name: StringAttr<'cx>,
+            ) -> Result<InnerRefAttr<'cx>, Error> {
+                let raw = unsafe {
+                    ffi::hwInnerRefAttrGet(
+                        Attribute::from(module).as_raw().into(),
+                        Attribute::from(name).as_raw().into(),
+                    )
+                };
+
+                unsafe { InnerRefAttr::from_raw(raw.into()) }
+                    .ok_or_else(|| null_handle("InnerRefAttr"))
+            }
+
+            pub fn module(&self) -> StringAttr<'cx> {
+                string_attr(unsafe { ffi::hwInnerRefAttrGetModule(self.as_raw().into()) })
+            }
+
+            pub fn name(&self) -> StringAttr<'cx> {
+                string_attr(unsafe { ffi::hwInnerRefAttrGetName(self.as_raw().into()) })
+            }
+        }
+
+        impl<'cx> InnerSymAttr<'cx> {
+            pub fn new(sym_name: StringAttr<'cx>) -> InnerSymAttr<'cx> {
+                InnerSymAttr::try_new(sym_name)
+                    .expect("unexpected MLIR error: InnerSymAttr should be non-null")
+            }
+
+            pub fn try_new(sym_name: StringAttr<'cx>) -> Result<InnerSymAttr<'cx>, Error> {
+                let raw =
+                    unsafe { ffi::hwInnerSymAttrGet(Attribute::from(sym_name).as_raw().into()) };
+
+                unsafe { InnerSymAttr::from_raw(raw.into()) }
+                    .ok_or_else(|| null_handle("InnerSymAttr"))
+            }
+
+            /// Creates an inner symbol attribute with no symbols.
+            pub fn empty(cx: &'cx Context) -> InnerSymAttr<'cx> {
+                InnerSymAttr::try_empty(cx)
+                    .expect("unexpected MLIR error: InnerSymAttr should be non-null")
+            }
+
+            /// Creates an inner symbol attribute with no symbols.
+            pub fn try_empty(cx: &'cx Context) -> Result<InnerSymAttr<'cx>, Error> {
+                unsafe {
+                    InnerSymAttr::from_raw(ffi::hwInnerSymAttrGetEmpty(cx.as_raw().into()).into())
+                }
+                .ok_or_else(|| null_handle("InnerSymAttr"))
+            }
+
+            pub fn sym_name(&self) -> StringAttr<'cx> {
+                string_attr(unsafe { ffi::hwInnerSymAttrGetSymName(self.as_raw().into()) })
+            }
+        }
+
+        impl<'cx> ParamDeclAttr<'cx> {
+            /// Creates a declaration of the module parameter `name` with the given type and
+            /// default value.
+            pub fn new<'a, S: Into<StringRef<'a>>>(
+                name: S,
+                ty: Type<'cx>,
+                value: Attribute<'cx>,
+            ) -> ParamDeclAttr<'cx> {
+                ParamDeclAttr::try_new(name, ty, value)
+                    .expect("unexpected MLIR error: ParamDeclAttr should be non-null")
+            }
+
+            /// Creates a declaration of the module parameter `name` with the given type and
+            /// default value.
+            pub fn try_new<'a, S: Into<StringRef<'a>>>(
+                name: S,
+                ty: Type<'cx>,
+                value: Attribute<'cx>,
+            ) -> Result<ParamDeclAttr<'cx>, Error> {
+                let raw = unsafe {
+                    ffi::hwParamDeclAttrGet(
+                        name.into().as_raw().into(),
+                        ty.as_raw().into(),
+                        value.as_raw().into(),
+                    )
+                };
+
+                unsafe { ParamDeclAttr::from_raw(raw.into()) }
+                    .ok_or_else(|| null_handle("ParamDeclAttr"))
+            }
+
+            pub fn name(&self) -> StringRef<'cx> {
+                unsafe {
+                    StringRef::from_raw(ffi::hwParamDeclAttrGetName(self.as_raw().into()).into())
+                }
+            }
+
+            pub fn ty(&self) -> Type<'cx> {
+                unsafe { Type::from_raw(ffi::hwParamDeclAttrGetType(self.as_raw().into()).into()) }
+                    .expect("unexpected MLIR error: ParamDeclAttr type should be non-null")
+            }
+
+            /// Returns the default value of the parameter, if any.
+            pub fn value(&self) -> Option<Attribute<'cx>> {
+                unsafe {
+                    Attribute::from_raw(ffi::hwParamDeclAttrGetValue(self.as_raw().into()).into())
+                }
+            }
+        }
+
+        impl<'cx> ParamDeclRefAttr<'cx> {
+            /// Creates a reference to the module parameter `name`.
+            pub fn new<'a, S: Into<StringRef<'a>>>(
+                cx: &'cx Context,
+                name: S,
+            ) -> ParamDeclRefAttr<'cx> {
+                ParamDeclRefAttr::try_new(cx, name)
+                    .expect("unexpected MLIR error: ParamDeclRefAttr should be non-null")
+            }
+
+            /// Creates a reference to the module parameter `name`.
+            pub fn try_new<'a, S: Into<StringRef<'a>>>(
+                cx: &'cx Context,
+                name: S,
+            ) -> Result<ParamDeclRefAttr<'cx>, Error> {
+                let raw = unsafe {
+                    ffi::hwParamDeclRefAttrGet(cx.as_raw().into(), name.into().as_raw().into())
+                };
+
+                unsafe { ParamDeclRefAttr::from_raw(raw.into()) }
+                    .ok_or_else(|| null_handle("ParamDeclRefAttr"))
+            }
+
+            pub fn name(&self) -> StringRef<'cx> {
+                unsafe {
+                    StringRef::from_raw(ffi::hwParamDeclRefAttrGetName(self.as_raw().into()).into())
+                }
+            }
+
+            pub fn ty(&self) -> Type<'cx> {
+                unsafe {
+                    Type::from_raw(ffi::hwParamDeclRefAttrGetType(self.as_raw().into()).into())
+                }
+                .expect("unexpected MLIR error: ParamDeclRefAttr type should be non-null")
+            }
+        }
+
+        impl<'cx> ParamVerbatimAttr<'cx> {
+            /// Creates a parameter expression which is emitted verbatim.
+            pub fn new(text: StringAttr<'cx>) -> ParamVerbatimAttr<'cx> {
+                ParamVerbatimAttr::try_new(text)
+                    .expect("unexpected MLIR error: ParamVerbatimAttr should be non-null")
+            }
+
+            /// Creates a parameter expression which is emitted verbatim.
+            pub fn try_new(text: StringAttr<'cx>) -> Result<ParamVerbatimAttr<'cx>, Error> {
+                let raw =
+                    unsafe { ffi::hwParamVerbatimAttrGet(Attribute::from(text).as_raw().into()) };
+
+                unsafe { ParamVerbatimAttr::from_raw(raw.into()) }
+                    .ok_or_else(|| null_handle("ParamVerbatimAttr"))
+            }
+        }
+    }
+
     pub mod ty {
         use super::*;
 
         is_fns_ext! {
-            pub trait TypeExt: Type {
+            pub trait TypeExt: Type<'cx> {
                 fn is_hw_array_type = ffi::hwTypeIsAArrayType;
                 fn is_hw_inout = ffi::hwTypeIsAInOut;
                 fn is_hw_int_type = ffi::hwTypeIsAIntType;
@@ -58,27 +252,28 @@ pub mod hw {
         }
 
         #[repr(C)]
-        pub struct StructFieldInfo {
-            pub name: Identifier,
-            pub ty: Type,
+        pub struct StructFieldInfo<'cx> {
+            pub name: Identifier<'cx>,
+            pub ty: Type<'cx>,
         }
 
         const _: () =
             assert!(mem::size_of::<StructFieldInfo>() == mem::size_of::<ffi::HWStructFieldInfo>());
 
-        pub fn struct_ty(elements: &[StructFieldInfo]) -> Option<Type> {
-            mlir::context().without_mutex(|cx| {
-                unsafe {
-                    let raw = ffi::hwStructTypeGet(
-                        cx.into(),
-                        elements.len() as isize,
-                        // SAFETY: StructFieldInfo is #[repr(C)] and its fields are transparent wrappers
-                        // around their underlying types.
-                        elements.as_ptr() as *const ffi::HWStructFieldInfo,
-                    );
-                    Type::from_raw(raw.into())
-                }
-            })
+        pub fn struct_ty<'cx>(
+            cx: &'cx Context,
+            elements: &[StructFieldInfo<'cx>],
+        ) -> Option<Type<'cx>> {
+            unsafe {
+                let raw = ffi::hwStructTypeGet(
+                    cx.as_raw().into(),
+                    elements.len() as isize,
+                    // SAFETY: StructFieldInfo is #[repr(C)] and its fields are transparent wrappers
+                    // around their underlying types.
+                    elements.as_ptr() as *const ffi::HWStructFieldInfo,
+                );
+                Type::from_raw(raw.into())
+            }
         }
     }
 
@@ -92,34 +287,34 @@ pub mod hw {
 
     /// The name, type and direction of a single port.
     #[derive(Clone)]
-    pub struct PortInfo {
-        pub name: Identifier,
+    pub struct PortInfo<'cx> {
+        pub name: Identifier<'cx>,
         pub direction: PortDirection,
-        pub ty: Type,
-        pub sym: Option<Identifier>,
+        pub ty: Type<'cx>,
+        pub sym: Option<Identifier<'cx>>,
     }
 
     /// The ports of a module.
     #[derive(Default)]
-    pub struct ModulePortInfo {
-        pub inputs: Vec<PortInfo>,
-        pub outputs: Vec<PortInfo>,
+    pub struct ModulePortInfo<'cx> {
+        pub inputs: Vec<PortInfo<'cx>>,
+        pub outputs: Vec<PortInfo<'cx>>,
     }
 
-    pub struct ModuleOpInfo<'a> {
+    pub struct ModuleOpInfo<'cx, 'a> {
         pub name: StringRef<'a>,
-        pub ports: ModulePortInfo,
-        pub params: ArrayAttr,
-        pub comment: Option<Identifier>,
-        pub attrs: &'a [NamedAttribute],
+        pub ports: ModulePortInfo<'cx>,
+        pub params: ArrayAttr<'cx>,
+        pub comment: Option<Identifier<'cx>>,
+        pub attrs: &'a [NamedAttribute<'cx>],
     }
 
-    fn export_port(sym: Identifier) -> NamedAttribute {
-        NamedAttribute::get("hw.exportPort", FlatSymbolRefAttr::new(sym.value()))
+    fn export_port<'cx>(cx: &'cx Context, sym: Identifier<'cx>) -> NamedAttribute<'cx> {
+        NamedAttribute::get("hw.exportPort", FlatSymbolRefAttr::new(cx, sym.value()))
     }
 
     /// Wraps inout parameters in `InOutType` and returns as a `Type`.
-    fn wrap_inout(port: &PortInfo) -> Type {
+    fn wrap_inout<'cx>(port: &PortInfo<'cx>) -> Type<'cx> {
         if port.direction == PortDirection::InOut && !port.ty.is_hw_inout() {
             todo!("wrap ty in InOutType")
         } else {
@@ -128,10 +323,11 @@ pub mod hw {
     }
 
     /// Generate the attributes dict for a port.
-    fn port_attrs(port: &PortInfo) -> Attribute {
+    fn port_attrs<'cx>(cx: &'cx Context, port: &PortInfo<'cx>) -> Attribute<'cx> {
         DictionaryAttr::create(
+            cx,
             port.sym
-                .map(export_port)
+                .map(|sym| export_port(cx, sym))
                 .as_ref()
                 .map(std::slice::from_ref)
                 .unwrap_or_default(),
@@ -139,15 +335,18 @@ pub mod hw {
         .into()
     }
 
-    pub struct ModuleOp {
-        op: Operation,
+    pub struct ModuleOp<'cx> {
+        op: Operation<'cx>,
     }
 
-    impl ModuleOp {
+    impl<'cx> ModuleOp<'cx> {
         // See `buildModule()` in HWOps.cpp
-        pub fn build(info: ModuleOpInfo<'_>) -> ModuleOp {
-            let mut state = OperationState::get("hw.module", Location::unknown());
-            state.add_attribute(SymbolTable::symbol_attribute_name(), info.name);
+        pub fn build(cx: &'cx Context, info: ModuleOpInfo<'cx, '_>) -> ModuleOp<'cx> {
+            let mut state = OperationState::get("hw.module", Location::unknown(cx));
+            state.add_attribute(
+                SymbolTable::symbol_attribute_name(),
+                Attribute::string(cx, info.name),
+            );
 
             let (arg_names, arg_types, arg_attrs) = info
                 .ports
@@ -157,7 +356,7 @@ pub mod hw {
                     (
                         Attribute::from(port.name),
                         wrap_inout(port),
-                        port_attrs(port),
+                        port_attrs(cx, port),
                     )
                 })
                 .multiunzip::<(Vec<Attribute>, Vec<Type>, Vec<Attribute>)>();
@@ -166,23 +365,26 @@ pub mod hw {
                 .ports
                 .outputs
                 .iter()
-                .map(|port| (Attribute::from(port.name), port.ty, port_attrs(port)))
+                .map(|port| (Attribute::from(port.name), port.ty, port_attrs(cx, port)))
                 .multiunzip::<(Vec<Attribute>, Vec<Type>, Vec<Attribute>)>();
 
-            let ty = FunctionType::get(&arg_types, &result_types);
+            let ty = FunctionType::get(cx, &arg_types, &result_types);
 
             state.add_attribute(FUNCTION_TYPE_ATTR_NAME, TypeAttr::get(ty.into()));
-            state.add_attribute("argNames", ArrayAttr::create(&arg_names));
-            state.add_attribute("resultNames", ArrayAttr::create(&result_names));
-            state.add_attribute(FUNCTION_ARG_DICT_ATTR_NAME, ArrayAttr::create(&arg_attrs));
+            state.add_attribute("argNames", ArrayAttr::create(cx, &arg_names));
+            state.add_attribute("resultNames", ArrayAttr::create(cx, &result_names));
+            state.add_attribute(
+                FUNCTION_ARG_DICT_ATTR_NAME,
+                ArrayAttr::create(cx, &arg_attrs),
+            );
             state.add_attribute(
                 FUNCTION_RESULT_DICT_ATTR_NAME,
-                ArrayAttr::create(&result_attrs),
+                ArrayAttr::create(cx, &result_attrs),
             );
             state.add_attribute("parameters", info.params);
             state.add_attribute(
                 "comment",
-                info.comment.unwrap_or_else(|| Identifier::get("")),
+                info.comment.unwrap_or_else(|| Identifier::get(cx, "")),
             );
             state.add_attributes(info.attrs);
 
@@ -190,7 +392,7 @@ pub mod hw {
             let mut block = region.append_block(Block::create());
 
             for input in &info.ports.inputs {
-                block.add_argument(input.ty, Location::unknown());
+                block.add_argument(input.ty, Location::unknown(cx));
             }
 
             todo!()
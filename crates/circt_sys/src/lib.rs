@@ -23,6 +23,25 @@ macro_rules! mlir_type_conversions {
 }
 
 mlir_type_conversions! {
+    MlirAttribute,
     MlirContext,
     MlirType,
 }
+
+impl From<mlir_sys::MlirStringRef> for crate::MlirStringRef {
+    fn from(value: mlir_sys::MlirStringRef) -> crate::MlirStringRef {
+        crate::MlirStringRef {
+            data: value.data,
+            length: value.length,
+        }
+    }
+}
+
+impl From<crate::MlirStringRef> for mlir_sys::MlirStringRef {
+    fn from(value: crate::MlirStringRef) -> mlir_sys::MlirStringRef {
+        mlir_sys::MlirStringRef {
+            data: value.data,
+            length: value.length,
+        }
+    }
+}
@@ -3,76 +3,143 @@
 //! Attributes are compile-time values associated with IR constructs and operated on by the MLIR
 //! runtime.
 
-use crate::{ffi, Attribute, NamedAttribute, StringRef, Type};
+use std::{ffi::c_int, iter::FusedIterator};
+
+#[cfg(feature = "num-bigint")]
+use num_bigint::{BigInt, BigUint, Sign};
+
+use crate::{
+    ffi, ty::IntegerType, AffineMap, Attribute, Context, Error, IntegerSet, NamedAttribute,
+    StringRef, Type,
+};
+
+mod dense;
+
+pub use dense::{BF16Bits, DenseElement, F16Bits};
 
 pub const FUNCTION_TYPE_ATTR_NAME: &str = "function_type";
 pub const FUNCTION_ARG_DICT_ATTR_NAME: &str = "arg_attrs";
 pub const FUNCTION_RESULT_DICT_ATTR_NAME: &str = "res_attrs";
 
+/// Defines wrappers around attribute subtypes.
+///
+/// Subtypes given an FFI predicate, e.g. `pub struct FooAttr = ffi::fooAttrIsAFoo;`, can also be
+/// downcast from `Attribute`. This is exported so that dialect crates can define their own
+/// attributes; the predicate may take any raw attribute type convertible from
+/// `mlir_sys::MlirAttribute`.
+#[doc(hidden)]
+#[macro_export]
 macro_rules! attr_types {
     ($(
-        $v:vis struct $name:ident;
+        $v:vis struct $name:ident $(= $is_fn:path)?;
     )*) => {
         $(
             #[derive(Copy, Clone)]
             #[repr(transparent)]
-            $v struct $name {
-                inner: Attribute,
+            $v struct $name<'cx> {
+                inner: $crate::Attribute<'cx>,
             }
 
-            impl From<$name> for Attribute {
-                fn from(other: $name) -> Attribute {
+            impl<'cx> From<$name<'cx>> for $crate::Attribute<'cx> {
+                fn from(other: $name<'cx>) -> $crate::Attribute<'cx> {
                     other.inner
                 }
             }
 
-            impl PartialEq for $name {
-                fn eq(&self, other: &Self) -> bool {
-                    self.inner == other.inner
-                }
-            }
-
-            impl Eq for $name {}
-
-            impl std::fmt::Display for $name {
-                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                    std::fmt::Display::fmt(&self.inner, f)
-                }
-            }
+            $crate::impl_subtype_traits!($name => $crate::Attribute<'_>);
 
-            impl $name {
+            impl<'cx> $name<'cx> {
                 #[allow(dead_code)]
-                pub(crate) unsafe fn from_raw(attr: ffi::MlirAttribute) -> Option<$name> {
+                pub(crate) unsafe fn from_raw(attr: $crate::ffi::MlirAttribute) -> Option<$name<'cx>> {
                     Some($name {
-                        inner: Attribute::from_raw(attr)?,
+                        inner: unsafe { $crate::Attribute::from_raw(attr) }?,
                     })
                 }
 
                 #[allow(dead_code)]
-                pub fn as_raw(&self) -> ffi::MlirAttribute {
-                    self.inner.inner
+                pub fn as_raw(&self) -> $crate::ffi::MlirAttribute {
+                    self.inner.as_raw()
                 }
             }
+
+            $(
+                $crate::attr_subtype! {
+                    $name: |attr| unsafe { $is_fn(attr.as_raw().into()) }
+                }
+            )?
         )*
     };
 }
 
+/// Implements downcasting from `Attribute` for a type defined by [`attr_types!`].
+///
+/// `$can_downcast` is evaluated with `$attr: &Attribute` bound to the attribute being checked.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! attr_subtype {
+    ($name:ident: |$attr:ident| $can_downcast:expr) => {
+        unsafe impl<'cx> $crate::attr::AttributeSubtype<'cx> for $name<'cx> {
+            fn can_downcast($attr: &$crate::Attribute<'cx>) -> bool {
+                $can_downcast
+            }
+
+            unsafe fn downcast_from_unchecked(attr: $crate::Attribute<'cx>) -> Self {
+                $name { inner: attr }
+            }
+        }
+
+        impl<'cx> TryFrom<$crate::Attribute<'cx>> for $name<'cx> {
+            type Error = $crate::Error;
+
+            fn try_from(attr: $crate::Attribute<'cx>) -> Result<Self, $crate::Error> {
+                attr.downcast().map_err(|attr: $crate::Attribute<'cx>| {
+                    $crate::Error::TypeMismatch {
+                        expected: stringify!($name),
+                        found: attr.to_string(),
+                    }
+                })
+            }
+        }
+
+        impl<'cx> $name<'cx> {
+            /// Parses an attribute from its textual form, checking that it is of this kind.
+            #[allow(dead_code)]
+            pub fn parse<'a, S: Into<$crate::StringRef<'a>>>(
+                cx: &'cx $crate::Context,
+                s: S,
+            ) -> Result<$name<'cx>, $crate::Error> {
+                $crate::Attribute::parse(cx, s)?.try_into()
+            }
+        }
+    };
+}
+
 macro_rules! attr_ctors {
     (
         $(
-            $v:vis fn $name:ident::$fn_name:ident($(
+            $v:vis fn $name:ident::$fn_name:ident / $try_fn_name:ident
+            $(<$lt:lifetime $(, $gen:ident : $bound:path)*>)? ($(
                 $arg:ident : $arg_ty:ty
             ),*) = $ctor_fn:path;
         )*
     ) => {
         $(
-            impl $name {
-                $v fn $fn_name($(
+            impl<'cx> $name<'cx> {
+                $v fn $fn_name $(<$lt $(, $gen : $bound)*>)? (cx: &'cx Context, $(
                     $arg : $arg_ty
-                ),*) -> $name {
-                    crate::context().without_mutex(|cx| unsafe {
-                        $name::from_raw($ctor_fn(cx, $($arg.as_raw()),*)).unwrap()
-                    })
+                ),*) -> $name<'cx> {
+                    $name::$try_fn_name(cx, $($arg),*).expect(concat!(
+                        "unexpected MLIR error: ",
+                        stringify!($name),
+                        " should be non-null"
+                    ))
+                }
+
+                $v fn $try_fn_name $(<$lt $(, $gen : $bound)*>)? (cx: &'cx Context, $(
+                    $arg : $arg_ty
+                ),*) -> Result<$name<'cx>, Error> {
+                    unsafe { $name::from_raw($ctor_fn(cx.as_raw(), $($arg.into().as_raw()),*)) }
+                        .ok_or_else(|| Error::null_handle(stringify!($name)))
                 }
             }
         )*
@@ -85,7 +152,7 @@ macro_rules! attr_getters {
         $v:vis fn $name:ident::$fn_name:ident(&self) -> $ret:ty = $getter_fn:path;
     )*) => {
         $(
-            impl $name {
+            impl<'cx> $name<'cx> {
                 $v fn $fn_name(&self) -> $ret {
                     unsafe {
                         <$ret>::from_raw($getter_fn(self.inner.inner))
@@ -96,60 +163,979 @@ macro_rules! attr_getters {
     };
 }
 
+/// A trait for subtypes of [`Attribute`].
+///
+/// # Safety
+///
+/// Implementors of this trait must uphold the following invariants:
+/// - `can_downcast` may return `true` only if the concrete type of `attr` is `Self`.
+/// - `downcast_from` must return the same object passed in, and may only return `Ok` if
+///   `can_downcast` returned `true` for `attr`.
+pub unsafe trait AttributeSubtype<'cx>: Sized {
+    /// Returns `true` if and only if `Self` is the concrete type of `attr`.
+    fn can_downcast(attr: &Attribute<'cx>) -> bool;
+
+    /// Downcasts from `Attribute` to `Self` without checking invariants.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call if and only if `Self` is the concrete type of `attr`.
+    unsafe fn downcast_from_unchecked(attr: Attribute<'cx>) -> Self;
+
+    fn downcast_from(attr: Attribute<'cx>) -> Result<Self, Attribute<'cx>> {
+        if Self::can_downcast(&attr) {
+            Ok(unsafe { Self::downcast_from_unchecked(attr) })
+        } else {
+            Err(attr)
+        }
+    }
+}
+
+macro_rules! attr_downcast {
+    ($($fn_name:ident => $subtype_name:ident),* $(,)?) => {
+        $(
+            $crate::attr_subtype! {
+                $subtype_name: |attr| Attribute::$fn_name(attr)
+            }
+        )*
+    };
+}
+
 attr_types! {
+    pub struct AffineMapAttr;
     pub struct ArrayAttr;
+    pub struct BoolAttr;
+    pub struct DenseElementsAttr;
+    pub struct DenseResourceElementsAttr;
     pub struct DictionaryAttr;
     pub struct FlatSymbolRefAttr;
+    pub struct FloatAttr;
+    pub struct IntegerAttr;
+    pub struct IntegerSetAttr;
+    pub struct OpaqueAttr;
+    pub struct StringAttr;
+    pub struct SymbolRefAttr;
     pub struct TypeAttr;
-    // pub struct StringAttr;
+    pub struct UnitAttr;
 }
 
 attr_ctors! {
-    pub fn FlatSymbolRefAttr::new( symbol: StringRef<'_>) = ffi::mlirFlatSymbolRefAttrGet;
+    pub fn FlatSymbolRefAttr::new / try_new<'a, S: Into<StringRef<'a>>>(symbol: S) = ffi::mlirFlatSymbolRefAttrGet;
+    pub fn StringAttr::new / try_new<'a, S: Into<StringRef<'a>>>(value: S) = ffi::mlirStringAttrGet;
+    pub fn UnitAttr::new / try_new() = ffi::mlirUnitAttrGet;
+}
+
+attr_downcast! {
+    is_affine_map => AffineMapAttr,
+    is_array => ArrayAttr,
+    is_bool => BoolAttr,
+    is_dense_elements => DenseElementsAttr,
+    is_dense_resource_elements => DenseResourceElementsAttr,
+    is_dictionary => DictionaryAttr,
+    is_flat_symbol_ref => FlatSymbolRefAttr,
+    is_float => FloatAttr,
+    is_integer => IntegerAttr,
+    is_integer_set => IntegerSetAttr,
+    is_opaque => OpaqueAttr,
+    is_string => StringAttr,
+    is_symbol_ref => SymbolRefAttr,
+    is_type => TypeAttr,
+    is_unit => UnitAttr,
 }
 
 attr_getters! {
-    pub fn FlatSymbolRefAttr::value(&self) -> StringRef = ffi::mlirFlatSymbolRefAttrGetValue;
+    pub fn FlatSymbolRefAttr::value(&self) -> StringRef<'cx> = ffi::mlirFlatSymbolRefAttrGetValue;
+    pub fn OpaqueAttr::dialect_namespace(&self) -> StringRef<'cx> = ffi::mlirOpaqueAttrGetDialectNamespace;
+    pub fn OpaqueAttr::data(&self) -> StringRef<'cx> = ffi::mlirOpaqueAttrGetData;
+    pub fn StringAttr::value(&self) -> StringRef<'cx> = ffi::mlirStringAttrGetValue;
+    pub fn SymbolRefAttr::root_reference(&self) -> StringRef<'cx> = ffi::mlirSymbolRefAttrGetRootReference;
+    pub fn SymbolRefAttr::leaf_reference(&self) -> StringRef<'cx> = ffi::mlirSymbolRefAttrGetLeafReference;
 }
 
-impl ArrayAttr {
-    pub fn create(elements: &[Attribute]) -> ArrayAttr {
-        crate::context()
-            .without_mutex(|cx| unsafe {
-                ArrayAttr::from_raw(ffi::mlirArrayAttrGet(
-                    cx,
-                    elements.len() as isize,
-                    elements.as_ptr().cast(),
-                ))
-            })
-            .unwrap()
+impl<'cx> AffineMapAttr<'cx> {
+    pub fn new(map: AffineMap<'cx>) -> AffineMapAttr<'cx> {
+        AffineMapAttr::try_new(map)
+            .expect("unexpected MLIR error: AffineMapAttr should be non-null")
+    }
+
+    pub fn try_new(map: AffineMap<'cx>) -> Result<AffineMapAttr<'cx>, Error> {
+        unsafe { AffineMapAttr::from_raw(ffi::mlirAffineMapAttrGet(map.as_raw())) }
+            .ok_or_else(|| Error::null_handle("AffineMapAttr"))
+    }
+
+    pub fn value(&self) -> AffineMap<'cx> {
+        unsafe {
+            AffineMap::from_raw(ffi::mlirAffineMapAttrGetValue(self.as_raw()))
+                .expect("unexpected MLIR error: AffineMapAttr value should be non-null")
+        }
     }
 }
 
-impl DictionaryAttr {
-    pub fn create(elements: &[NamedAttribute]) -> DictionaryAttr {
-        crate::context()
-            .without_mutex(|cx| unsafe {
-                DictionaryAttr::from_raw(ffi::mlirDictionaryAttrGet(
-                    cx,
-                    elements.len() as isize,
-                    elements.as_ptr().cast(),
-                ))
-            })
-            .unwrap()
+impl<'cx> ArrayAttr<'cx> {
+    pub fn create(cx: &'cx Context, elements: &[Attribute<'cx>]) -> ArrayAttr<'cx> {
+        ArrayAttr::try_create(cx, elements)
+            .expect("unexpected MLIR error: ArrayAttr should be non-null")
+    }
+
+    pub fn try_create(
+        cx: &'cx Context,
+        elements: &[Attribute<'cx>],
+    ) -> Result<ArrayAttr<'cx>, Error> {
+        unsafe {
+            ArrayAttr::from_raw(ffi::mlirArrayAttrGet(
+                cx.as_raw(),
+                elements.len() as isize,
+                elements.as_ptr().cast(),
+            ))
+        }
+        .ok_or_else(|| Error::null_handle("ArrayAttr"))
+    }
+
+    /// Creates an array attribute from the attributes yielded by `iter`, which may be empty.
+    pub fn from_iter_in<I>(cx: &'cx Context, iter: I) -> ArrayAttr<'cx>
+    where
+        I: IntoIterator<Item = Attribute<'cx>>,
+    {
+        ArrayAttr::create(cx, &iter.into_iter().collect::<Vec<_>>())
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { ffi::mlirArrayAttrGetNumElements(self.as_raw()) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the element at `index`, or `None` if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> Option<Attribute<'cx>> {
+        if index >= self.len() {
+            return None;
+        }
+
+        unsafe { Attribute::from_raw(ffi::mlirArrayAttrGetElement(self.as_raw(), index as isize)) }
+    }
+
+    pub fn iter(&self) -> ArrayElements<'cx> {
+        ArrayElements::new(*self)
     }
 }
 
-impl From<Type> for TypeAttr {
-    fn from(value: Type) -> Self {
+impl<'cx> IntoIterator for ArrayAttr<'cx> {
+    type Item = Attribute<'cx>;
+    type IntoIter = ArrayElements<'cx>;
+
+    fn into_iter(self) -> ArrayElements<'cx> {
+        self.iter()
+    }
+}
+
+impl<'cx> IntoIterator for &ArrayAttr<'cx> {
+    type Item = Attribute<'cx>;
+    type IntoIter = ArrayElements<'cx>;
+
+    fn into_iter(self) -> ArrayElements<'cx> {
+        self.iter()
+    }
+}
+
+impl<'cx> BoolAttr<'cx> {
+    pub fn new(cx: &'cx Context, value: bool) -> BoolAttr<'cx> {
+        BoolAttr::try_new(cx, value).expect("unexpected MLIR error: BoolAttr should be non-null")
+    }
+
+    pub fn try_new(cx: &'cx Context, value: bool) -> Result<BoolAttr<'cx>, Error> {
+        unsafe { BoolAttr::from_raw(ffi::mlirBoolAttrGet(cx.as_raw(), value as c_int)) }
+            .ok_or_else(|| Error::null_handle("BoolAttr"))
+    }
+
+    pub fn value(&self) -> bool {
+        unsafe { ffi::mlirBoolAttrGetValue(self.as_raw()) }
+    }
+}
+
+impl<'cx> DictionaryAttr<'cx> {
+    pub fn create(cx: &'cx Context, elements: &[NamedAttribute<'cx>]) -> DictionaryAttr<'cx> {
+        DictionaryAttr::try_create(cx, elements)
+            .expect("unexpected MLIR error: DictionaryAttr should be non-null")
+    }
+
+    pub fn try_create(
+        cx: &'cx Context,
+        elements: &[NamedAttribute<'cx>],
+    ) -> Result<DictionaryAttr<'cx>, Error> {
+        unsafe {
+            DictionaryAttr::from_raw(ffi::mlirDictionaryAttrGet(
+                cx.as_raw(),
+                elements.len() as isize,
+                elements.as_ptr().cast(),
+            ))
+        }
+        .ok_or_else(|| Error::null_handle("DictionaryAttr"))
+    }
+
+    /// Creates a dictionary attribute from the named attributes yielded by `iter`, which may be
+    /// empty.
+    pub fn from_iter_in<I>(cx: &'cx Context, iter: I) -> DictionaryAttr<'cx>
+    where
+        I: IntoIterator<Item = NamedAttribute<'cx>>,
+    {
+        DictionaryAttr::create(cx, &iter.into_iter().collect::<Vec<_>>())
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { ffi::mlirDictionaryAttrGetNumElements(self.as_raw()) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the attribute named `name`, or `None` if there is no such attribute.
+    pub fn get<'a, S: Into<StringRef<'a>>>(&self, name: S) -> Option<Attribute<'cx>> {
+        unsafe {
+            Attribute::from_raw(ffi::mlirDictionaryAttrGetElementByName(
+                self.as_raw(),
+                name.into().as_raw(),
+            ))
+        }
+    }
+
+    /// Returns the element at `index`, or `None` if `index` is out of bounds.
+    ///
+    /// Elements are sorted by name.
+    pub fn element(&self, index: usize) -> Option<NamedAttribute<'cx>> {
+        if index >= self.len() {
+            return None;
+        }
+
+        unsafe {
+            NamedAttribute::from_raw(ffi::mlirDictionaryAttrGetElement(
+                self.as_raw(),
+                index as isize,
+            ))
+        }
+    }
+
+    /// Returns an iterator over the elements, sorted by name.
+    pub fn iter(&self) -> DictionaryElements<'cx> {
+        DictionaryElements::new(*self)
+    }
+}
+
+impl<'cx> IntoIterator for DictionaryAttr<'cx> {
+    type Item = NamedAttribute<'cx>;
+    type IntoIter = DictionaryElements<'cx>;
+
+    fn into_iter(self) -> DictionaryElements<'cx> {
+        self.iter()
+    }
+}
+
+impl<'cx> IntoIterator for &DictionaryAttr<'cx> {
+    type Item = NamedAttribute<'cx>;
+    type IntoIter = DictionaryElements<'cx>;
+
+    fn into_iter(self) -> DictionaryElements<'cx> {
+        self.iter()
+    }
+}
+
+impl<'cx> FloatAttr<'cx> {
+    /// Creates a float attribute of type `ty`, rounding `value` to its precision.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ty` is not a float type. See [`FloatAttr::try_new`].
+    pub fn new(ty: Type<'cx>, value: f64) -> FloatAttr<'cx> {
+        FloatAttr::try_new(ty, value).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a float attribute of type `ty`, rounding `value` to its precision.
+    ///
+    /// Returns [`Error::TypeMismatch`] if `ty` is not a float type.
+    pub fn try_new(ty: Type<'cx>, value: f64) -> Result<FloatAttr<'cx>, Error> {
+        if !ty.is_float() {
+            return Err(Error::TypeMismatch {
+                expected: "float type",
+                found: ty.to_string(),
+            });
+        }
+
+        let cx = ty.context();
+        let (raw, diagnostics) = cx.with_diagnostics(|| unsafe {
+            let loc = ffi::mlirLocationUnknownGet(cx.as_raw());
+            ffi::mlirFloatAttrDoubleGetChecked(loc, ty.as_raw(), value)
+        });
+
+        unsafe { FloatAttr::from_raw(raw) }.ok_or(Error::NullHandle {
+            kind: "FloatAttr",
+            diagnostics,
+        })
+    }
+
+    /// Returns the value of the attribute, converted to an `f64`.
+    pub fn value(&self) -> f64 {
+        unsafe { ffi::mlirFloatAttrGetValueDouble(self.as_raw()) }
+    }
+}
+
+impl<'cx> IntegerAttr<'cx> {
+    /// Creates an integer attribute of type `ty`.
+    ///
+    /// `value` is truncated to the width of `ty`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ty` is not an integer or index type. See [`IntegerAttr::try_new`].
+    pub fn new(ty: Type<'cx>, value: i64) -> IntegerAttr<'cx> {
+        IntegerAttr::try_new(ty, value).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates an integer attribute of type `ty`.
+    ///
+    /// `value` is truncated to the width of `ty`. Returns [`Error::TypeMismatch`] if `ty` is not
+    /// an integer or index type.
+    pub fn try_new(ty: Type<'cx>, value: i64) -> Result<IntegerAttr<'cx>, Error> {
+        if !ty.is_integer() && !ty.is_index() {
+            return Err(Error::TypeMismatch {
+                expected: "integer or index type",
+                found: ty.to_string(),
+            });
+        }
+
+        unsafe { IntegerAttr::from_raw(ffi::mlirIntegerAttrGet(ty.as_raw(), value)) }
+            .ok_or_else(|| Error::null_handle("IntegerAttr"))
+    }
+
+    /// Returns the value of a signless or index attribute, sign-extended to 64 bits.
+    ///
+    /// Returns `None` if the attribute is of signed or unsigned type, or wider than 64 bits. Use
+    /// [`IntegerAttr::signed_value`] or [`IntegerAttr::unsigned_value`] for attributes of signed
+    /// or unsigned type.
+    pub fn value(&self) -> Option<i64> {
+        let ty = self.inner.ty();
+        let signless = ty.is_index() || self.integer_type().is_some_and(|ty| ty.is_signless());
+
+        // `IntegerAttr::getInt` aborts on signed and unsigned types.
+        signless.then(|| unsafe { ffi::mlirIntegerAttrGetValueInt(self.as_raw()) })
+    }
+
+    /// Returns the value of an attribute of signed type.
+    ///
+    /// Returns `None` if the attribute is not of signed type, or wider than 64 bits.
+    pub fn signed_value(&self) -> Option<i64> {
+        let signed = self.integer_type().is_some_and(|ty| ty.is_signed());
+        signed.then(|| unsafe { ffi::mlirIntegerAttrGetValueSInt(self.as_raw()) })
+    }
+
+    /// Returns the value of an attribute of unsigned type.
+    ///
+    /// Returns `None` if the attribute is not of unsigned type, or wider than 64 bits.
+    pub fn unsigned_value(&self) -> Option<u64> {
+        let unsigned = self.integer_type().is_some_and(|ty| ty.is_unsigned());
+        unsigned.then(|| unsafe { ffi::mlirIntegerAttrGetValueUInt(self.as_raw()) })
+    }
+
+    /// Returns the type of the attribute if it is an integer type of at most 64 bits.
+    fn integer_type(&self) -> Option<IntegerType<'cx>> {
+        // The C API accessors abort on values which do not fit in 64 bits.
+        self.inner
+            .ty()
+            .downcast::<IntegerType>()
+            .ok()
+            .filter(|ty| ty.width() <= 64)
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl<'cx> IntegerAttr<'cx> {
+    /// Creates an integer attribute of type `ty` with an arbitrary-precision value.
+    ///
+    /// Unlike [`IntegerAttr::new`], the value is not truncated. Returns an error if `ty` is not
+    /// an integer or index type, or if `value` does not fit in it. Signless and index types accept
+    /// both signed and unsigned values of their width; since they store only the bits, values
+    /// above the signed range read back from [`IntegerAttr::to_biguint`] rather than
+    /// [`IntegerAttr::to_bigint`].
+    pub fn from_bigint(ty: Type<'cx>, value: &BigInt) -> Result<IntegerAttr<'cx>, Error> {
+        let (width, signed, unsigned) = bigint_kind(ty)?;
+
+        let fits = match value.sign() {
+            Sign::NoSign => true,
+            Sign::Minus => !unsigned && (-value - 1u8).bits() < width,
+            Sign::Plus if signed => value.bits() < width,
+            Sign::Plus => value.bits() <= width,
+        };
+        if !fits {
+            return Err(Error::OutOfRange {
+                value: value.to_string(),
+                ty: ty.to_string(),
+            });
+        }
+
+        // The parser rejects index values above the signed range, so pass their two's
+        // complement equivalent instead.
+        let value = if ty.is_index() && value.sign() == Sign::Plus && value.bits() >= width {
+            value - (BigInt::from(1u8) << width)
+        } else {
+            value.clone()
+        };
+
+        // The C API only accepts 64-bit values, so go through the textual form.
+        let src = format!("{value} : {ty}");
+        let src = StringRef::from(src.as_str());
+        let (attr, diagnostics) = ty.context().with_diagnostics(|| unsafe {
+            Attribute::from_raw(ffi::mlirAttributeParseGet(
+                ffi::mlirTypeGetContext(ty.as_raw()),
+                src.as_raw(),
+            ))
+        });
+
+        attr.and_then(|attr| attr.downcast::<IntegerAttr>().ok())
+            .ok_or(Error::Parse { diagnostics })
+    }
+
+    /// Returns the value of the attribute with arbitrary precision.
+    ///
+    /// Values of signless and index type are interpreted as signed, as in
+    /// [`IntegerAttr::value`], except that `i1` values are 0 or 1. See
+    /// [`IntegerAttr::to_biguint`] to interpret them as unsigned.
+    pub fn to_bigint(&self) -> BigInt {
+        let (width, signed, unsigned) = bigint_kind(self.inner.ty())
+            .expect("unexpected MLIR error: IntegerAttr should have an integer or index type");
+        let raw = self.as_raw();
+
+        // The C API accessors assert that the type has the matching signedness.
+        if width <= 64 {
+            return unsafe {
+                if signed {
+                    BigInt::from(ffi::mlirIntegerAttrGetValueSInt(raw))
+                } else if unsigned {
+                    BigInt::from(ffi::mlirIntegerAttrGetValueUInt(raw))
+                } else if width == 1 {
+                    BigInt::from(ffi::mlirIntegerAttrGetValueInt(raw) & 1)
+                } else {
+                    BigInt::from(ffi::mlirIntegerAttrGetValueInt(raw))
+                }
+            };
+        }
+
+        let mut words = vec![0u64; width.div_ceil(64) as usize];
+        unsafe { ffi::mlirRsIntegerAttrGetValueWords(raw, words.as_mut_ptr()) };
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        let value = BigInt::from_bytes_le(Sign::Plus, &bytes);
+
+        // Reinterpret the bits as two's complement.
+        if !unsigned && value.bit(width - 1) {
+            value - (BigInt::from(1u8) << width)
+        } else {
+            value
+        }
+    }
+
+    /// Returns the value of the attribute with arbitrary precision, interpreting values of
+    /// signless and index type as unsigned.
+    ///
+    /// Returns `None` if the attribute is of signed type and its value is negative.
+    pub fn to_biguint(&self) -> Option<BigUint> {
+        let value = self.to_bigint();
+        if value.sign() != Sign::Minus {
+            return value.to_biguint();
+        }
+
+        let (width, signed, _) = bigint_kind(self.inner.ty()).ok()?;
+        if signed {
+            return None;
+        }
+
+        // Reinterpret the two's complement bits as unsigned.
+        (value + (BigInt::from(1u8) << width)).to_biguint()
+    }
+}
+
+// Returns the width and signedness of an integer or index type, as `(width, signed, unsigned)`.
+// Index types are treated as signless, since they have no signedness of their own.
+#[cfg(feature = "num-bigint")]
+fn bigint_kind(ty: Type<'_>) -> Result<(u64, bool, bool), Error> {
+    if ty.is_index() {
+        Ok((64, false, false))
+    } else if let Ok(int) = ty.downcast::<IntegerType>() {
+        Ok((int.width() as u64, int.is_signed(), int.is_unsigned()))
+    } else {
+        Err(Error::TypeMismatch {
+            expected: "integer or index type",
+            found: ty.to_string(),
+        })
+    }
+}
+
+impl<'cx> IntegerSetAttr<'cx> {
+    pub fn new(set: IntegerSet<'cx>) -> IntegerSetAttr<'cx> {
+        IntegerSetAttr::try_new(set)
+            .expect("unexpected MLIR error: IntegerSetAttr should be non-null")
+    }
+
+    pub fn try_new(set: IntegerSet<'cx>) -> Result<IntegerSetAttr<'cx>, Error> {
+        unsafe { IntegerSetAttr::from_raw(ffi::mlirIntegerSetAttrGet(set.as_raw())) }
+            .ok_or_else(|| Error::null_handle("IntegerSetAttr"))
+    }
+
+    pub fn value(&self) -> IntegerSet<'cx> {
+        unsafe {
+            IntegerSet::from_raw(ffi::mlirIntegerSetAttrGetValue(self.as_raw()))
+                .expect("unexpected MLIR error: IntegerSetAttr value should be non-null")
+        }
+    }
+}
+
+impl<'cx> OpaqueAttr<'cx> {
+    /// Creates an attribute belonging to an unregistered dialect, e.g. `#namespace<data>`.
+    pub fn new<'a, S: Into<StringRef<'a>>>(
+        cx: &'cx Context,
+        namespace: S,
+        data: &[u8],
+        ty: Type<'cx>,
+    ) -> OpaqueAttr<'cx> {
+        OpaqueAttr::try_new(cx, namespace, data, ty)
+            .expect("unexpected MLIR error: OpaqueAttr should be non-null")
+    }
+
+    pub fn try_new<'a, S: Into<StringRef<'a>>>(
+        cx: &'cx Context,
+        namespace: S,
+        data: &[u8],
+        ty: Type<'cx>,
+    ) -> Result<OpaqueAttr<'cx>, Error> {
+        unsafe {
+            OpaqueAttr::from_raw(ffi::mlirOpaqueAttrGet(
+                cx.as_raw(),
+                namespace.into().inner,
+                data.len() as isize,
+                data.as_ptr().cast(),
+                ty.as_raw(),
+            ))
+        }
+        .ok_or_else(|| Error::null_handle("OpaqueAttr"))
+    }
+}
+
+impl<'cx> StringAttr<'cx> {
+    /// Creates a string attribute with the given type, e.g. `"value" : i32`.
+    pub fn typed<'a, S: Into<StringRef<'a>>>(ty: Type<'cx>, value: S) -> StringAttr<'cx> {
+        StringAttr::try_typed(ty, value)
+            .expect("unexpected MLIR error: StringAttr should be non-null")
+    }
+
+    pub fn try_typed<'a, S: Into<StringRef<'a>>>(
+        ty: Type<'cx>,
+        value: S,
+    ) -> Result<StringAttr<'cx>, Error> {
+        unsafe {
+            StringAttr::from_raw(ffi::mlirStringAttrTypedGet(ty.as_raw(), value.into().inner))
+        }
+        .ok_or_else(|| Error::null_handle("StringAttr"))
+    }
+
+    /// Returns the type of the string, or `None` if it was created without one.
+    pub fn ty(&self) -> Option<Type<'cx>> {
+        let ty = self.inner.ty();
+        (!ty.is_none()).then_some(ty)
+    }
+}
+
+impl<'cx> SymbolRefAttr<'cx> {
+    /// Creates a reference to the symbol `root`, nested through the symbols in `nested`, e.g.
+    /// `@root::@a::@b`.
+    pub fn new<'a, S: Into<StringRef<'a>>>(
+        cx: &'cx Context,
+        root: S,
+        nested: &[FlatSymbolRefAttr<'cx>],
+    ) -> SymbolRefAttr<'cx> {
+        SymbolRefAttr::try_new(cx, root, nested)
+            .expect("unexpected MLIR error: SymbolRefAttr should be non-null")
+    }
+
+    pub fn try_new<'a, S: Into<StringRef<'a>>>(
+        cx: &'cx Context,
+        root: S,
+        nested: &[FlatSymbolRefAttr<'cx>],
+    ) -> Result<SymbolRefAttr<'cx>, Error> {
+        unsafe {
+            SymbolRefAttr::from_raw(ffi::mlirSymbolRefAttrGet(
+                cx.as_raw(),
+                root.into().inner,
+                nested.len() as isize,
+                nested.as_ptr().cast(),
+            ))
+        }
+        .ok_or_else(|| Error::null_handle("SymbolRefAttr"))
+    }
+
+    pub fn num_nested_references(&self) -> usize {
+        unsafe { ffi::mlirSymbolRefAttrGetNumNestedReferences(self.as_raw()) as usize }
+    }
+
+    /// Returns the nested reference at `index`, or `None` if `index` is out of bounds.
+    pub fn nested_reference(&self, index: usize) -> Option<FlatSymbolRefAttr<'cx>> {
+        if index >= self.num_nested_references() {
+            return None;
+        }
+
+        unsafe {
+            FlatSymbolRefAttr::from_raw(ffi::mlirSymbolRefAttrGetNestedReference(
+                self.as_raw(),
+                index as isize,
+            ))
+        }
+    }
+
+    /// Returns an iterator over the nested references.
+    pub fn nested_references(&self) -> impl ExactSizeIterator<Item = FlatSymbolRefAttr<'cx>> + '_ {
+        (0..self.num_nested_references()).map(|i| {
+            self.nested_reference(i)
+                .expect("unexpected MLIR error: nested reference should be non-null")
+        })
+    }
+}
+
+impl<'cx> From<FlatSymbolRefAttr<'cx>> for SymbolRefAttr<'cx> {
+    fn from(attr: FlatSymbolRefAttr<'cx>) -> SymbolRefAttr<'cx> {
+        SymbolRefAttr { inner: attr.inner }
+    }
+}
+
+impl<'cx> From<Type<'cx>> for TypeAttr<'cx> {
+    fn from(value: Type<'cx>) -> Self {
         TypeAttr::get(value)
     }
 }
 
-impl TypeAttr {
-    pub fn get(ty: Type) -> TypeAttr {
-        crate::context()
-            .without_mutex(|cx| unsafe { TypeAttr::from_raw(ffi::mlirTypeAttrGet(ty.as_raw())) })
-            .unwrap()
+impl<'cx> TypeAttr<'cx> {
+    pub fn get(ty: Type<'cx>) -> TypeAttr<'cx> {
+        TypeAttr::try_get(ty).expect("unexpected MLIR error: TypeAttr should be non-null")
+    }
+
+    pub fn try_get(ty: Type<'cx>) -> Result<TypeAttr<'cx>, Error> {
+        unsafe { TypeAttr::from_raw(ffi::mlirTypeAttrGet(ty.as_raw())) }
+            .ok_or_else(|| Error::null_handle("TypeAttr"))
+    }
+}
+
+// Defines iterators over the elements of collection attributes.
+macro_rules! attr_iters {
+    ($(
+        $(#[$attr:meta])*
+        $v:vis struct $name:ident: $parent:ident => $item:ident {
+            get: $get_fn:ident,
+        }
+    )*) => {
+        $(
+            $(#[$attr])*
+            $v struct $name<'cx> {
+                parent: $parent<'cx>,
+                front: usize,
+                back: usize,
+            }
+
+            impl<'cx> $name<'cx> {
+                fn new(parent: $parent<'cx>) -> $name<'cx> {
+                    $name {
+                        parent,
+                        front: 0,
+                        back: parent.len(),
+                    }
+                }
+            }
+
+            impl<'cx> Iterator for $name<'cx> {
+                type Item = $item<'cx>;
+
+                fn next(&mut self) -> Option<Self::Item> {
+                    if self.front == self.back {
+                        return None;
+                    }
+
+                    let item = self.parent.$get_fn(self.front);
+                    self.front += 1;
+                    item
+                }
+
+                fn size_hint(&self) -> (usize, Option<usize>) {
+                    let len = self.back - self.front;
+                    (len, Some(len))
+                }
+            }
+
+            impl<'cx> DoubleEndedIterator for $name<'cx> {
+                fn next_back(&mut self) -> Option<Self::Item> {
+                    if self.front == self.back {
+                        return None;
+                    }
+
+                    self.back -= 1;
+                    self.parent.$get_fn(self.back)
+                }
+            }
+
+            impl ExactSizeIterator for $name<'_> {}
+
+            impl FusedIterator for $name<'_> {}
+        )*
+    };
+}
+
+attr_iters! {
+    /// An iterator over the elements of an [`ArrayAttr`].
+    pub struct ArrayElements: ArrayAttr => Attribute {
+        get: get,
+    }
+
+    /// An iterator over the elements of a [`DictionaryAttr`], sorted by name.
+    pub struct DictionaryElements: DictionaryAttr => NamedAttribute {
+        get: element,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ty::{F32Type, IndexType, IntegerType};
+
+    #[test]
+    fn integer_attr_type() {
+        let cx = Context::create().unwrap();
+        let i8 = IntegerType::signless(&cx, 8).into();
+        assert_eq!(IntegerAttr::try_new(i8, -1).unwrap().value(), Some(-1));
+        assert_eq!(
+            IntegerAttr::try_new(IndexType::get(&cx).into(), 7)
+                .unwrap()
+                .value(),
+            Some(7)
+        );
+        assert!(matches!(
+            IntegerAttr::try_new(F32Type::get(&cx).into(), 1),
+            Err(Error::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn integer_attr_accessors() {
+        let cx = Context::create().unwrap();
+
+        let si8 = IntegerAttr::new(IntegerType::signed(&cx, 8).into(), -2);
+        assert_eq!(si8.value(), None);
+        assert_eq!(si8.signed_value(), Some(-2));
+        assert_eq!(si8.unsigned_value(), None);
+
+        let ui8 = IntegerAttr::new(IntegerType::unsigned(&cx, 8).into(), 255);
+        assert_eq!(ui8.value(), None);
+        assert_eq!(ui8.signed_value(), None);
+        assert_eq!(ui8.unsigned_value(), Some(255));
+
+        let i128 = IntegerAttr::new(IntegerType::signless(&cx, 128).into(), 1);
+        assert_eq!(i128.value(), None);
+        assert_eq!(i128.signed_value(), None);
+        assert_eq!(i128.unsigned_value(), None);
+    }
+
+    #[test]
+    fn array_attr() {
+        let cx = Context::create().unwrap();
+        let elements: [Attribute; 2] = [BoolAttr::new(&cx, true).into(), UnitAttr::new(&cx).into()];
+
+        let array = ArrayAttr::from_iter_in(&cx, elements);
+        assert_eq!(array.len(), 2);
+        assert_eq!(array.get(0), Some(elements[0]));
+        assert_eq!(array.get(1), Some(elements[1]));
+        assert_eq!(array.get(2), None);
+        assert_eq!(array.iter().collect::<Vec<_>>(), elements);
+        assert_eq!(
+            array.iter().rev().collect::<Vec<_>>(),
+            [elements[1], elements[0]]
+        );
+        assert_eq!(array, ArrayAttr::create(&cx, &elements));
+
+        let empty = ArrayAttr::from_iter_in(&cx, []);
+        assert!(empty.is_empty());
+        assert_eq!(empty.iter().next(), None);
+        assert_eq!(empty.to_string(), "[]");
+    }
+
+    #[test]
+    fn dictionary_attr() {
+        let cx = Context::create().unwrap();
+        let (a, b): (Attribute, Attribute) =
+            (BoolAttr::new(&cx, true).into(), UnitAttr::new(&cx).into());
+
+        // Elements are sorted by name.
+        let dict = DictionaryAttr::from_iter_in(
+            &cx,
+            [NamedAttribute::get("b", b), NamedAttribute::get("a", a)],
+        );
+        assert_eq!(dict.len(), 2);
+        assert_eq!(dict.get("a"), Some(a));
+        assert_eq!(dict.get("b"), Some(b));
+        assert_eq!(dict.get("c"), None);
+        assert_eq!(dict.element(0).unwrap().name.value().to_str().unwrap(), "a");
+        assert!(dict.element(2).is_none());
+
+        let names: Vec<_> = dict
+            .iter()
+            .map(|named| named.name.value().to_str().unwrap().to_owned())
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+        assert_eq!(
+            dict.iter().map(|named| named.attribute).collect::<Vec<_>>(),
+            [a, b]
+        );
+
+        let empty = DictionaryAttr::from_iter_in(&cx, []);
+        assert!(empty.is_empty());
+        assert_eq!(empty.to_string(), "{}");
+    }
+
+    #[cfg(feature = "num-bigint")]
+    #[test]
+    fn bigint_boundaries() {
+        let cx = Context::create().unwrap();
+        let int = |s: &str| s.parse::<BigInt>().unwrap();
+        let uint = |s: &str| s.parse::<BigUint>().unwrap();
+        let round_trip = |ty: Type, value: &BigInt| {
+            IntegerAttr::from_bigint(ty, value).map(|attr| (attr.to_bigint(), attr.to_biguint()))
+        };
+
+        let (i128_min, i128_max) = (BigInt::from(i128::MIN), BigInt::from(i128::MAX));
+        let u128_max = BigInt::from(u128::MAX);
+        let one = BigInt::from(1);
+
+        let si128 = IntegerType::signed(&cx, 128).into();
+        assert_eq!(
+            round_trip(si128, &i128_min).unwrap(),
+            (i128_min.clone(), None)
+        );
+        assert_eq!(round_trip(si128, &i128_max).unwrap().0, i128_max);
+        assert!(matches!(
+            round_trip(si128, &(&i128_max + &one)),
+            Err(Error::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            round_trip(si128, &(&i128_min - &one)),
+            Err(Error::OutOfRange { .. })
+        ));
+
+        let ui128 = IntegerType::unsigned(&cx, 128).into();
+        assert_eq!(
+            round_trip(ui128, &u128_max).unwrap(),
+            (u128_max.clone(), Some(BigUint::from(u128::MAX)))
+        );
+        assert!(matches!(
+            round_trip(ui128, &(&u128_max + &one)),
+            Err(Error::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            round_trip(ui128, &-&one),
+            Err(Error::OutOfRange { .. })
+        ));
+
+        // Signless types hold both signed and unsigned values, which share their bits.
+        let i128 = IntegerType::signless(&cx, 128).into();
+        assert_eq!(round_trip(i128, &i128_min).unwrap().0, i128_min);
+        assert_eq!(
+            round_trip(i128, &u128_max).unwrap(),
+            (-&one, Some(BigUint::from(u128::MAX)))
+        );
+        assert!(matches!(
+            round_trip(i128, &(&u128_max + &one)),
+            Err(Error::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            round_trip(i128, &(&i128_min - &one)),
+            Err(Error::OutOfRange { .. })
+        ));
+
+        let i256 = IntegerType::signless(&cx, 256).into();
+        let i256_max = (BigInt::from(1) << 255) - 1;
+        assert_eq!(round_trip(i256, &i256_max).unwrap().0, i256_max);
+        let u256_max = (BigInt::from(1) << 256) - 1;
+        assert_eq!(
+            round_trip(i256, &u256_max).unwrap().1,
+            Some(uint(&u256_max.to_string()))
+        );
+
+        let index = IndexType::get(&cx).into();
+        assert_eq!(round_trip(index, &int("-1")).unwrap().0, int("-1"));
+        let i64_min = BigInt::from(i64::MIN);
+        assert_eq!(round_trip(index, &i64_min).unwrap().0, i64_min);
+        assert_eq!(
+            round_trip(index, &BigInt::from(u64::MAX)).unwrap().1,
+            Some(BigUint::from(u64::MAX))
+        );
+        assert!(matches!(
+            round_trip(index, &(BigInt::from(u64::MAX) + 1)),
+            Err(Error::OutOfRange { .. })
+        ));
+
+        // Widths up to 64 bits read through the C API, and wider ones as words.
+        let si8 = IntegerType::signed(&cx, 8).into();
+        assert_eq!(round_trip(si8, &int("-128")).unwrap(), (int("-128"), None));
+        let ui64 = IntegerType::unsigned(&cx, 64).into();
+        let u64_max = BigInt::from(u64::MAX);
+        assert_eq!(round_trip(ui64, &u64_max).unwrap().0, u64_max);
+        let si100 = IntegerType::signed(&cx, 100).into();
+        let i100_min = -(BigInt::from(1) << 99);
+        assert_eq!(round_trip(si100, &i100_min).unwrap().0, i100_min);
+
+        let i1 = IntegerType::signless(&cx, 1).into();
+        assert_eq!(
+            round_trip(i1, &one).unwrap(),
+            (one.clone(), Some(BigUint::from(1u8)))
+        );
+    }
+
+    #[test]
+    fn float_attr_type() {
+        let cx = Context::create().unwrap();
+        assert_eq!(
+            FloatAttr::try_new(F32Type::get(&cx).into(), 0.5)
+                .unwrap()
+                .value(),
+            0.5
+        );
+        assert!(matches!(
+            FloatAttr::try_new(IndexType::get(&cx).into(), 0.5),
+            Err(Error::TypeMismatch { .. })
+        ));
+    }
+
+    // Defined as a dialect crate would, to exercise the `= is_fn` arm of `attr_types!`.
+    attr_types! {
+        struct TestStringAttr = ffi::mlirAttributeIsAString;
+    }
+
+    #[test]
+    fn external_attr_types() {
+        let cx = Context::create().unwrap();
+        let string: Attribute = StringAttr::new(&cx, "hello").into();
+        let unit: Attribute = UnitAttr::new(&cx).into();
+
+        let downcast = string.downcast::<TestStringAttr>().unwrap();
+        assert_eq!(Attribute::from(downcast), string);
+        assert_eq!(downcast.to_string(), "\"hello\"");
+        assert_eq!(TestStringAttr::try_from(string).unwrap(), downcast);
+
+        assert_eq!(unit.downcast::<TestStringAttr>().unwrap_err(), unit);
+        match TestStringAttr::try_from(unit) {
+            Err(Error::TypeMismatch { expected, found }) => {
+                assert_eq!(expected, "TestStringAttr");
+                assert_eq!(found, "unit");
+            }
+            other => panic!("expected a type mismatch, got {other:?}"),
+        }
+
+        assert_eq!(TestStringAttr::parse(&cx, "\"hello\"").unwrap(), downcast);
+        assert!(matches!(
+            TestStringAttr::parse(&cx, "unit"),
+            Err(Error::TypeMismatch { .. })
+        ));
     }
 }
@@ -0,0 +1,943 @@
+//! Dense elements attributes, e.g. `dense<[1, 2, 3]> : tensor<3xi32>`, and their resource-backed
+//! counterparts, e.g. `dense_resource<blob> : tensor<3xi32>`.
+
+use std::{
+    ffi::{c_int, c_void},
+    panic::{self, AssertUnwindSafe},
+};
+
+#[cfg(feature = "ndarray")]
+use ndarray::{ArrayBase, ArrayD, Data, Dimension, IxDyn};
+
+#[cfg(feature = "ndarray")]
+use crate::ty::{Dim, RankedTensorType};
+use crate::{
+    attr::{DenseElementsAttr, DenseResourceElementsAttr},
+    ffi, private,
+    ty::{FloatType, IntegerType, ShapedType},
+    Attribute, Error, StringRef, Type,
+};
+
+/// A Rust type which can be stored in a [`DenseElementsAttr`].
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait DenseElement: Copy + private::Sealed {
+    /// Returns `true` if values of this type can be stored as elements of type `ty`.
+    fn is_element_type(ty: Type<'_>) -> bool;
+
+    #[doc(hidden)]
+    unsafe fn get_raw(ty: ffi::MlirType, values: &[Self]) -> ffi::MlirAttribute;
+
+    #[doc(hidden)]
+    unsafe fn value_raw(attr: ffi::MlirAttribute, index: isize) -> Self;
+
+    #[doc(hidden)]
+    unsafe fn resource_value_raw(attr: ffi::MlirAttribute, index: isize) -> Self;
+
+    // The C API has no accessors for resource elements of some types.
+    #[doc(hidden)]
+    const HAS_RESOURCE_VALUES: bool = true;
+}
+
+/// The bit pattern of an IEEE 754 half-precision float, stored as an `f16` element.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct F16Bits(pub u16);
+
+/// The bit pattern of a bfloat16 float, stored as a `bf16` element.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct BF16Bits(pub u16);
+
+// Mirrors the element type check in MLIR's `DenseElementsAttr::get`. Signless integers may hold
+// either signed or unsigned values, and `index` is stored as a 64-bit integer.
+fn is_int_element_type(ty: Type<'_>, width: u32, signed: bool) -> bool {
+    if ty.is_index() {
+        return width == 64;
+    }
+
+    match ty.downcast::<IntegerType>() {
+        Ok(ty) => ty.width() == width && (ty.is_signless() || ty.is_signed() == signed),
+        Err(_) => false,
+    }
+}
+
+macro_rules! dense_int_elements {
+    ($(
+        $t:ty, $signed:literal => $get_fn:path, $value_fn:path, $resource_value_fn:path;
+    )*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl DenseElement for $t {
+                fn is_element_type(ty: Type<'_>) -> bool {
+                    is_int_element_type(ty, <$t>::BITS, $signed)
+                }
+
+                unsafe fn get_raw(ty: ffi::MlirType, values: &[Self]) -> ffi::MlirAttribute {
+                    $get_fn(ty, values.len() as isize, values.as_ptr())
+                }
+
+                unsafe fn value_raw(attr: ffi::MlirAttribute, index: isize) -> Self {
+                    $value_fn(attr, index)
+                }
+
+                unsafe fn resource_value_raw(attr: ffi::MlirAttribute, index: isize) -> Self {
+                    $resource_value_fn(attr, index)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! dense_float_elements {
+    ($(
+        $t:ty, $width:literal => $get_fn:path, $value_fn:path, $resource_value_fn:path;
+    )*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl DenseElement for $t {
+                fn is_element_type(ty: Type<'_>) -> bool {
+                    ty.downcast::<FloatType>().is_ok_and(|ty| ty.width() == $width)
+                }
+
+                unsafe fn get_raw(ty: ffi::MlirType, values: &[Self]) -> ffi::MlirAttribute {
+                    $get_fn(ty, values.len() as isize, values.as_ptr())
+                }
+
+                unsafe fn value_raw(attr: ffi::MlirAttribute, index: isize) -> Self {
+                    $value_fn(attr, index)
+                }
+
+                unsafe fn resource_value_raw(attr: ffi::MlirAttribute, index: isize) -> Self {
+                    $resource_value_fn(attr, index)
+                }
+            }
+        )*
+    };
+}
+
+dense_int_elements! {
+    i8, true => ffi::mlirDenseElementsAttrInt8Get, ffi::mlirDenseElementsAttrGetInt8Value,
+        ffi::mlirDenseInt8ResourceElementsAttrGetValue;
+    u8, false => ffi::mlirDenseElementsAttrUInt8Get, ffi::mlirDenseElementsAttrGetUInt8Value,
+        ffi::mlirDenseUInt8ResourceElementsAttrGetValue;
+    i16, true => ffi::mlirDenseElementsAttrInt16Get, ffi::mlirDenseElementsAttrGetInt16Value,
+        ffi::mlirDenseInt16ResourceElementsAttrGetValue;
+    u16, false => ffi::mlirDenseElementsAttrUInt16Get, ffi::mlirDenseElementsAttrGetUInt16Value,
+        ffi::mlirDenseUInt16ResourceElementsAttrGetValue;
+    i32, true => ffi::mlirDenseElementsAttrInt32Get, ffi::mlirDenseElementsAttrGetInt32Value,
+        ffi::mlirDenseInt32ResourceElementsAttrGetValue;
+    u32, false => ffi::mlirDenseElementsAttrUInt32Get, ffi::mlirDenseElementsAttrGetUInt32Value,
+        ffi::mlirDenseUInt32ResourceElementsAttrGetValue;
+    i64, true => ffi::mlirDenseElementsAttrInt64Get, ffi::mlirDenseElementsAttrGetInt64Value,
+        ffi::mlirDenseInt64ResourceElementsAttrGetValue;
+    u64, false => ffi::mlirDenseElementsAttrUInt64Get, ffi::mlirDenseElementsAttrGetUInt64Value,
+        ffi::mlirDenseUInt64ResourceElementsAttrGetValue;
+}
+
+dense_float_elements! {
+    f32, 32 => ffi::mlirDenseElementsAttrFloatGet, ffi::mlirDenseElementsAttrGetFloatValue,
+        ffi::mlirDenseFloatResourceElementsAttrGetValue;
+    f64, 64 => ffi::mlirDenseElementsAttrDoubleGet, ffi::mlirDenseElementsAttrGetDoubleValue,
+        ffi::mlirDenseDoubleResourceElementsAttrGetValue;
+}
+
+macro_rules! dense_half_elements {
+    ($(
+        $t:ident, $is_fn:ident => $get_fn:path;
+    )*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl DenseElement for $t {
+                fn is_element_type(ty: Type<'_>) -> bool {
+                    ty.$is_fn()
+                }
+
+                unsafe fn get_raw(ty: ffi::MlirType, values: &[Self]) -> ffi::MlirAttribute {
+                    $get_fn(ty, values.len() as isize, values.as_ptr().cast())
+                }
+
+                unsafe fn value_raw(attr: ffi::MlirAttribute, index: isize) -> Self {
+                    // There is no typed accessor, so read the raw storage, which holds a single
+                    // element if the attribute is a splat.
+                    let data = ffi::mlirDenseElementsAttrGetRawData(attr) as *const u16;
+                    let index = if ffi::mlirDenseElementsAttrIsSplat(attr) { 0 } else { index };
+                    $t(*data.offset(index))
+                }
+
+                unsafe fn resource_value_raw(_attr: ffi::MlirAttribute, _index: isize) -> Self {
+                    unreachable!(concat!("resource elements of type ", stringify!($t)))
+                }
+
+                const HAS_RESOURCE_VALUES: bool = false;
+            }
+        )*
+    };
+}
+
+dense_half_elements! {
+    F16Bits, is_f16 => ffi::mlirDenseElementsAttrFloat16Get;
+    BF16Bits, is_bf16 => ffi::mlirDenseElementsAttrBFloat16Get;
+}
+
+impl private::Sealed for bool {}
+
+impl DenseElement for bool {
+    fn is_element_type(ty: Type<'_>) -> bool {
+        ty.downcast::<IntegerType>()
+            .is_ok_and(|ty| ty.is_signless() && ty.width() == 1)
+    }
+
+    unsafe fn get_raw(ty: ffi::MlirType, values: &[Self]) -> ffi::MlirAttribute {
+        let values = values.iter().map(|&v| v as c_int).collect::<Vec<_>>();
+        ffi::mlirDenseElementsAttrBoolGet(ty, values.len() as isize, values.as_ptr())
+    }
+
+    unsafe fn value_raw(attr: ffi::MlirAttribute, index: isize) -> Self {
+        ffi::mlirDenseElementsAttrGetBoolValue(attr, index)
+    }
+
+    unsafe fn resource_value_raw(attr: ffi::MlirAttribute, index: isize) -> Self {
+        ffi::mlirDenseBoolResourceElementsAttrGetValue(attr, index)
+    }
+}
+
+// Returns the number of elements of `ty`, or an error if it does not have a static shape.
+fn static_num_elements<'cx, S: ShapedType<'cx>>(ty: S) -> Result<usize, Error> {
+    ty.num_elements()
+        .and_then(|n| usize::try_from(n).ok())
+        .ok_or_else(|| {
+            Error::InvalidArgument(format!("{} does not have a static shape", ty.into()))
+        })
+}
+
+// Checks that `ty` has a static shape whose elements can hold values of type `T`, and returns its
+// number of elements.
+fn check_shaped_type<'cx, S: ShapedType<'cx>, T: DenseElement>(ty: S) -> Result<usize, Error> {
+    let num_elements = static_num_elements(ty)?;
+
+    let element = ty.element_type();
+    if !T::is_element_type(element) {
+        return Err(Error::TypeMismatch {
+            expected: std::any::type_name::<T>(),
+            found: element.to_string(),
+        });
+    }
+
+    Ok(num_elements)
+}
+
+impl<'cx> DenseElementsAttr<'cx> {
+    /// Creates a dense attribute of type `ty` holding `values` in row-major order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ty` does not have a static shape with `values.len()` elements, or if its
+    /// element type cannot hold values of type `T`. See [`DenseElementsAttr::try_from_slice`].
+    pub fn from_slice<S, T>(ty: S, values: &[T]) -> DenseElementsAttr<'cx>
+    where
+        S: ShapedType<'cx>,
+        T: DenseElement,
+    {
+        DenseElementsAttr::try_from_slice(ty, values).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a dense attribute of type `ty` holding `values` in row-major order.
+    ///
+    /// Returns [`Error::InvalidArgument`] if `ty` does not have a static shape with
+    /// `values.len()` elements, or [`Error::TypeMismatch`] if its element type cannot hold values
+    /// of type `T`.
+    pub fn try_from_slice<S, T>(ty: S, values: &[T]) -> Result<DenseElementsAttr<'cx>, Error>
+    where
+        S: ShapedType<'cx>,
+        T: DenseElement,
+    {
+        let num_elements = check_shaped_type::<S, T>(ty)?;
+        if values.len() != num_elements {
+            return Err(Error::InvalidArgument(format!(
+                "expected {num_elements} values for {}, found {}",
+                ty.into(),
+                values.len(),
+            )));
+        }
+
+        unsafe { DenseElementsAttr::from_raw(T::get_raw(ty.into().as_raw(), values)) }
+            .ok_or_else(|| Error::null_handle("DenseElementsAttr"))
+    }
+
+    /// Creates a dense attribute of type `ty` whose elements are all `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ty` does not have a static shape, or if its element type cannot hold values of
+    /// type `T`. See [`DenseElementsAttr::try_splat`].
+    pub fn splat<S, T>(ty: S, value: T) -> DenseElementsAttr<'cx>
+    where
+        S: ShapedType<'cx>,
+        T: DenseElement,
+    {
+        DenseElementsAttr::try_splat(ty, value).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a dense attribute of type `ty` whose elements are all `value`.
+    ///
+    /// Returns [`Error::InvalidArgument`] if `ty` does not have a static shape, or
+    /// [`Error::TypeMismatch`] if its element type cannot hold values of type `T`.
+    pub fn try_splat<S, T>(ty: S, value: T) -> Result<DenseElementsAttr<'cx>, Error>
+    where
+        S: ShapedType<'cx>,
+        T: DenseElement,
+    {
+        check_shaped_type::<S, T>(ty)?;
+
+        // MLIR treats a single value as a splat of the whole shape.
+        unsafe { DenseElementsAttr::from_raw(T::get_raw(ty.into().as_raw(), &[value])) }
+            .ok_or_else(|| Error::null_handle("DenseElementsAttr"))
+    }
+
+    /// Creates a dense attribute of type `ty` from its raw storage.
+    ///
+    /// `data` holds the elements in row-major order, each padded to a whole number of bytes,
+    /// except for `i1` elements which are packed 8 to a byte. `data` may also hold a single
+    /// element, which is splatted over the whole shape.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ty` does not have a static shape, or if `data` is not a valid buffer for `ty`.
+    /// See [`DenseElementsAttr::try_from_raw_buffer`].
+    pub fn from_raw_buffer<S: ShapedType<'cx>>(ty: S, data: &[u8]) -> DenseElementsAttr<'cx> {
+        DenseElementsAttr::try_from_raw_buffer(ty, data).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a dense attribute of type `ty` from its raw storage.
+    ///
+    /// Returns [`Error::InvalidArgument`] if `ty` does not have a static shape, or an error if
+    /// `data` is not a valid buffer for `ty`.
+    pub fn try_from_raw_buffer<S: ShapedType<'cx>>(
+        ty: S,
+        data: &[u8],
+    ) -> Result<DenseElementsAttr<'cx>, Error> {
+        // MLIR asserts that the shape is static while validating the buffer.
+        static_num_elements(ty)?;
+
+        unsafe {
+            DenseElementsAttr::from_raw(ffi::mlirDenseElementsAttrRawBufferGet(
+                ty.into().as_raw(),
+                data.len(),
+                data.as_ptr() as *const c_void,
+            ))
+        }
+        .ok_or_else(|| Error::null_handle("DenseElementsAttr"))
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        unsafe { ffi::mlirElementsAttrGetNumElements(self.as_raw()) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if all elements have the same value.
+    pub fn is_splat(&self) -> bool {
+        unsafe { ffi::mlirDenseElementsAttrIsSplat(self.as_raw()) }
+    }
+
+    /// Returns the value of every element if the attribute is a splat.
+    pub fn splat_value(&self) -> Option<Attribute<'cx>> {
+        if !self.is_splat() {
+            return None;
+        }
+
+        unsafe { Attribute::from_raw(ffi::mlirDenseElementsAttrGetSplatValue(self.as_raw())) }
+    }
+
+    pub fn element_type(&self) -> Type<'cx> {
+        unsafe {
+            Type::from_raw(ffi::mlirShapedTypeGetElementType(self.inner.ty().as_raw()))
+                .expect("unexpected MLIR error: DenseElementsAttr element type should be non-null")
+        }
+    }
+
+    /// Returns the elements in row-major order.
+    ///
+    /// Returns an error if the element type cannot hold values of type `T`.
+    pub fn values<T: DenseElement>(&self) -> Result<Vec<T>, Error> {
+        let element = self.element_type();
+        if !T::is_element_type(element) {
+            return Err(Error::TypeMismatch {
+                expected: std::any::type_name::<T>(),
+                found: element.to_string(),
+            });
+        }
+
+        Ok((0..self.len())
+            .map(|i| unsafe { T::value_raw(self.as_raw(), i as isize) })
+            .collect())
+    }
+}
+
+// Returns the number of bytes used to store each element of type `ty` in a resource blob.
+fn resource_element_size(ty: Type<'_>) -> Option<usize> {
+    let width = if ty.is_index() {
+        64
+    } else if let Ok(ty) = ty.downcast::<IntegerType>() {
+        ty.width()
+    } else if let Ok(ty) = ty.downcast::<FloatType>() {
+        ty.width()
+    } else {
+        return None;
+    };
+
+    Some(width.div_ceil(8) as usize)
+}
+
+unsafe extern "C" fn drop_blob<B>(
+    userdata: *mut c_void,
+    _data: *const c_void,
+    _size: usize,
+    _align: usize,
+) {
+    // Unwinding out of an `extern "C"` function aborts, so a panic while dropping the blob is
+    // caught here instead.
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+        drop(unsafe { Box::from_raw(userdata as *mut B) });
+    }));
+}
+
+impl<'cx> DenseResourceElementsAttr<'cx> {
+    /// Creates a dense attribute of type `ty` whose elements are stored in `blob`, without
+    /// copying them.
+    ///
+    /// MLIR takes ownership of `blob` and drops it when the resource is released, which is at
+    /// the latest when the context is destroyed. `name` is the key of the resource, e.g.
+    /// `dense_resource<name>`; MLIR may append a suffix to make it unique. `blob` holds the
+    /// elements in row-major order, one element per `ceil(width / 8)` bytes, and must be aligned
+    /// to `alignment` bytes.
+    ///
+    /// Resources are included when the IR is written as bytecode.
+    ///
+    /// MLIR drops `blob` from C++, where a panic can't unwind; if dropping `blob` panics, the
+    /// panic is caught and discarded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ty` does not have a static shape with an integer, index or float element type,
+    /// if `blob` is not the size of the elements of `ty`, or if it is not aligned to `alignment`.
+    /// See [`DenseResourceElementsAttr::try_from_blob`].
+    pub fn from_blob<'a, S, N, B>(
+        ty: S,
+        name: N,
+        blob: B,
+        alignment: usize,
+    ) -> DenseResourceElementsAttr<'cx>
+    where
+        S: ShapedType<'cx>,
+        N: Into<StringRef<'a>>,
+        B: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        DenseResourceElementsAttr::try_from_blob(ty, name, blob, alignment)
+            .unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a dense attribute of type `ty` whose elements are stored in `blob`, without
+    /// copying them.
+    ///
+    /// Returns [`Error::InvalidArgument`] if `ty` does not have a static shape, if `blob` is not
+    /// the size of the elements of `ty`, or if it is not aligned to `alignment`, and
+    /// [`Error::TypeMismatch`] if the element type is not an integer, index or float type. On
+    /// error, `blob` is dropped before returning.
+    pub fn try_from_blob<'a, S, N, B>(
+        ty: S,
+        name: N,
+        blob: B,
+        alignment: usize,
+    ) -> Result<DenseResourceElementsAttr<'cx>, Error>
+    where
+        S: ShapedType<'cx>,
+        N: Into<StringRef<'a>>,
+        B: AsRef<[u8]> + Send + Sync + 'static,
+    {
+        let num_elements = static_num_elements(ty)?;
+        let element = ty.element_type();
+        let element_size = resource_element_size(element).ok_or_else(|| Error::TypeMismatch {
+            expected: "integer, index or float element type",
+            found: element.to_string(),
+        })?;
+
+        // Box the blob first so that the data doesn't move if it is stored inline.
+        let blob = Box::new(blob);
+        let data = (*blob).as_ref();
+
+        let size = num_elements.checked_mul(element_size);
+        if size != Some(data.len()) {
+            return Err(Error::InvalidArgument(format!(
+                "blob of {} bytes does not match {}",
+                data.len(),
+                ty.into(),
+            )));
+        }
+        if !alignment.is_power_of_two() || data.as_ptr() as usize % alignment != 0 {
+            return Err(Error::InvalidArgument(format!(
+                "blob is not aligned to {alignment} bytes"
+            )));
+        }
+
+        let (ptr, len) = (data.as_ptr(), data.len());
+        let userdata = Box::into_raw(blob);
+
+        // MLIR calls `drop_blob` when it releases the resource.
+        unsafe {
+            DenseResourceElementsAttr::from_raw(ffi::mlirUnmanagedDenseResourceElementsAttrGet(
+                ty.into().as_raw(),
+                name.into().as_raw(),
+                ptr as *mut c_void,
+                len,
+                alignment,
+                false,
+                Some(drop_blob::<B>),
+                userdata as *mut c_void,
+            ))
+        }
+        .ok_or_else(|| Error::null_handle("DenseResourceElementsAttr"))
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        unsafe { ffi::mlirElementsAttrGetNumElements(self.as_raw()) as usize }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn element_type(&self) -> Type<'cx> {
+        unsafe {
+            Type::from_raw(ffi::mlirShapedTypeGetElementType(self.inner.ty().as_raw())).expect(
+                "unexpected MLIR error: DenseResourceElementsAttr element type should be non-null",
+            )
+        }
+    }
+
+    /// Returns the elements in row-major order.
+    ///
+    /// Returns an error if the element type cannot hold values of type `T`, or
+    /// [`Error::InvalidArgument`] if:
+    ///
+    /// - elements of type `T` cannot be read from resources, which is the case for [`F16Bits`],
+    ///   [`BF16Bits`] and `index` elements;
+    /// - the resource holds no data, e.g. if it was parsed from `dense_resource<name>` without a
+    ///   resource section.
+    pub fn values<T: DenseElement>(&self) -> Result<Vec<T>, Error> {
+        let element = self.element_type();
+        if !T::is_element_type(element) {
+            return Err(Error::TypeMismatch {
+                expected: std::any::type_name::<T>(),
+                found: element.to_string(),
+            });
+        }
+
+        // The accessors for 64-bit integers only accept integer types, not `index`.
+        if !T::HAS_RESOURCE_VALUES || element.is_index() {
+            return Err(Error::InvalidArgument(format!(
+                "cannot read resource elements of type {element}"
+            )));
+        }
+
+        if !unsafe { ffi::mlirRsDenseResourceElementsAttrHasBlob(self.as_raw()) } {
+            return Err(Error::InvalidArgument(format!("{self} has no data")));
+        }
+
+        Ok((0..self.len())
+            .map(|i| unsafe { T::resource_value_raw(self.as_raw(), i as isize) })
+            .collect())
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<'cx> DenseElementsAttr<'cx> {
+    /// Creates a dense attribute of tensor type with the shape and elements of `array`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `element` cannot hold values of type `T`. See
+    /// [`DenseElementsAttr::try_from_array`].
+    pub fn from_array<T, S, D>(
+        element: Type<'cx>,
+        array: &ArrayBase<S, D>,
+    ) -> DenseElementsAttr<'cx>
+    where
+        T: DenseElement,
+        S: Data<Elem = T>,
+        D: Dimension,
+    {
+        DenseElementsAttr::try_from_array(element, array).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a dense attribute of tensor type with the shape and elements of `array`.
+    ///
+    /// Returns [`Error::TypeMismatch`] if `element` cannot hold values of type `T`.
+    pub fn try_from_array<T, S, D>(
+        element: Type<'cx>,
+        array: &ArrayBase<S, D>,
+    ) -> Result<DenseElementsAttr<'cx>, Error>
+    where
+        T: DenseElement,
+        S: Data<Elem = T>,
+        D: Dimension,
+    {
+        let shape = array
+            .shape()
+            .iter()
+            .map(|&dim| Dim::Static(dim as u64))
+            .collect::<Vec<_>>();
+        let ty = RankedTensorType::try_get(element, &shape, None)?;
+
+        // Iteration is in logical order, regardless of the array's memory layout.
+        let values = array.iter().copied().collect::<Vec<_>>();
+        DenseElementsAttr::try_from_slice(ty, &values)
+    }
+
+    /// Returns the elements as an array with the shape of the attribute's type.
+    ///
+    /// Returns an error if the element type cannot hold values of type `T`.
+    pub fn to_array<T: DenseElement>(&self) -> Result<ArrayD<T>, Error> {
+        let ty = self.inner.ty().as_raw();
+        let shape = unsafe {
+            (0..ffi::mlirShapedTypeGetRank(ty))
+                .map(|i| ffi::mlirShapedTypeGetDimSize(ty, i as isize) as usize)
+                .collect::<Vec<_>>()
+        };
+
+        let array = ArrayD::from_shape_vec(IxDyn(&shape), self.values()?)
+            .expect("unexpected MLIR error: DenseElementsAttr shape should match its elements");
+        Ok(array)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    use super::*;
+    use crate::{
+        bytecode::BytecodeConfig,
+        ty::{
+            BF16Type, ComplexType, Dim, F16Type, F32Type, IndexType, IntegerType, RankedTensorType,
+            UnrankedTensorType,
+        },
+        Context, Location, Module, Operation, OperationState,
+    };
+
+    fn tensor<'cx>(element: impl Into<Type<'cx>>, shape: &[u64]) -> RankedTensorType<'cx> {
+        let shape = shape
+            .iter()
+            .map(|&dim| Dim::Static(dim))
+            .collect::<Vec<_>>();
+        RankedTensorType::get(element.into(), &shape, None)
+    }
+
+    #[test]
+    fn from_slice() {
+        let cx = Context::create().unwrap();
+        let ty = tensor(IntegerType::signless(&cx, 32), &[2, 2]);
+
+        let attr = DenseElementsAttr::from_slice(ty, &[1i32, -2, 3, -4]);
+        assert_eq!(attr.len(), 4);
+        assert!(!attr.is_splat());
+        assert_eq!(
+            attr.to_string(),
+            "dense<[[1, -2], [3, -4]]> : tensor<2x2xi32>"
+        );
+        assert_eq!(attr.values::<i32>().unwrap(), [1, -2, 3, -4]);
+        assert!(matches!(
+            attr.values::<f32>(),
+            Err(Error::TypeMismatch { .. })
+        ));
+
+        let floats = tensor(F32Type::get(&cx), &[3]);
+        let attr = DenseElementsAttr::from_slice(floats, &[0.5f32, 1.0, 2.0]);
+        assert_eq!(attr.values::<f32>().unwrap(), [0.5, 1.0, 2.0]);
+
+        let bools = tensor(IntegerType::signless(&cx, 1), &[2]);
+        let attr = DenseElementsAttr::from_slice(bools, &[true, false]);
+        assert_eq!(attr.values::<bool>().unwrap(), [true, false]);
+    }
+
+    #[test]
+    fn invalid_slices() {
+        let cx = Context::create().unwrap();
+        let i32 = IntegerType::signless(&cx, 32).into();
+
+        let dynamic = RankedTensorType::get(i32, &[Dim::Dynamic], None);
+        assert!(matches!(
+            DenseElementsAttr::try_from_slice(dynamic, &[1i32]),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            DenseElementsAttr::try_splat(UnrankedTensorType::get(i32), 1i32),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            DenseElementsAttr::try_from_raw_buffer(dynamic, &[0; 4]),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        let ty = tensor(i32, &[3]);
+        assert!(matches!(
+            DenseElementsAttr::try_from_slice(ty, &[1i32, 2]),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            DenseElementsAttr::try_from_slice(ty, &[1u8, 2, 3]),
+            Err(Error::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            DenseElementsAttr::try_splat(ty, 1.0f64),
+            Err(Error::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn splat() {
+        let cx = Context::create().unwrap();
+        let ty = tensor(IntegerType::unsigned(&cx, 8), &[2, 3]);
+
+        let attr = DenseElementsAttr::splat(ty, 7u8);
+        assert!(attr.is_splat());
+        assert_eq!(attr.len(), 6);
+        assert_eq!(attr.values::<u8>().unwrap(), [7; 6]);
+        assert_eq!(attr.splat_value().unwrap().to_string(), "7 : ui8");
+    }
+
+    #[test]
+    fn raw_buffer() {
+        let cx = Context::create().unwrap();
+        let ty = tensor(IntegerType::signless(&cx, 16), &[2]);
+
+        let data = [1i16, 256].map(i16::to_ne_bytes).concat();
+        let attr = DenseElementsAttr::from_raw_buffer(ty, &data);
+        assert_eq!(attr.values::<i16>().unwrap(), [1, 256]);
+
+        let splat = DenseElementsAttr::from_raw_buffer(ty, &5i16.to_ne_bytes());
+        assert!(splat.is_splat());
+        assert_eq!(splat.values::<i16>().unwrap(), [5, 5]);
+
+        assert!(DenseElementsAttr::try_from_raw_buffer(ty, &[0; 3]).is_err());
+    }
+
+    #[test]
+    fn half_floats() {
+        let cx = Context::create().unwrap();
+
+        // 1.0 and -2.0 in each format.
+        let f16 = [F16Bits(0x3c00), F16Bits(0xc000)];
+        let attr = DenseElementsAttr::from_slice(tensor(F16Type::get(&cx), &[2]), &f16);
+        assert_eq!(
+            attr.to_string(),
+            "dense<[1.000000e+00, -2.000000e+00]> : tensor<2xf16>"
+        );
+        assert_eq!(attr.values::<F16Bits>().unwrap(), f16);
+        assert!(matches!(
+            attr.values::<BF16Bits>(),
+            Err(Error::TypeMismatch { .. })
+        ));
+
+        let bf16 = [BF16Bits(0x3f80), BF16Bits(0xc000)];
+        let attr = DenseElementsAttr::from_slice(tensor(BF16Type::get(&cx), &[2]), &bf16);
+        assert_eq!(attr.values::<BF16Bits>().unwrap(), bf16);
+
+        let splat = DenseElementsAttr::splat(tensor(F16Type::get(&cx), &[3]), F16Bits(0x3c00));
+        assert_eq!(splat.values::<F16Bits>().unwrap(), [F16Bits(0x3c00); 3]);
+    }
+
+    // A blob of `i32`s which records when it is dropped.
+    struct Blob {
+        data: Vec<i32>,
+        dropped: Arc<AtomicBool>,
+    }
+
+    impl Blob {
+        fn new(data: Vec<i32>) -> (Blob, Arc<AtomicBool>) {
+            let dropped = Arc::new(AtomicBool::new(false));
+            let blob = Blob {
+                data,
+                dropped: dropped.clone(),
+            };
+            (blob, dropped)
+        }
+    }
+
+    impl AsRef<[u8]> for Blob {
+        fn as_ref(&self) -> &[u8] {
+            unsafe { std::slice::from_raw_parts(self.data.as_ptr().cast(), self.data.len() * 4) }
+        }
+    }
+
+    impl Drop for Blob {
+        fn drop(&mut self) {
+            self.dropped.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn resource_blob_deleter() {
+        let cx = Context::create().unwrap();
+        let ty = tensor(IntegerType::signless(&cx, 32), &[3]);
+
+        let (blob, dropped) = Blob::new(vec![1, 2, 3]);
+        let attr = DenseResourceElementsAttr::from_blob(ty, "blob", blob, 4);
+        assert_eq!(attr.values::<i32>().unwrap(), [1, 2, 3]);
+        assert!(!dropped.load(Ordering::SeqCst));
+
+        drop(cx);
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn resource_blob_deleter_panics() {
+        struct PanickingBlob([u8; 4]);
+
+        impl AsRef<[u8]> for PanickingBlob {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl Drop for PanickingBlob {
+            fn drop(&mut self) {
+                panic!("dropping blob");
+            }
+        }
+
+        let cx = Context::create().unwrap();
+        let ty = tensor(IntegerType::signless(&cx, 8), &[4]);
+        DenseResourceElementsAttr::from_blob(ty, "blob", PanickingBlob([1, 2, 3, 4]), 1);
+
+        // Destroying the context releases the resource without unwinding into MLIR.
+        drop(cx);
+    }
+
+    #[test]
+    fn invalid_resource_blobs() {
+        let cx = Context::create().unwrap();
+        let ty = tensor(IntegerType::signless(&cx, 32), &[3]);
+
+        let (blob, dropped) = Blob::new(vec![1, 2]);
+        assert!(matches!(
+            DenseResourceElementsAttr::try_from_blob(ty, "blob", blob, 4),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(dropped.load(Ordering::SeqCst));
+
+        let (blob, _) = Blob::new(vec![1, 2, 3]);
+        assert!(matches!(
+            DenseResourceElementsAttr::try_from_blob(ty, "blob", blob, 3),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        let dynamic = RankedTensorType::get(ty.element_type(), &[Dim::Dynamic], None);
+        let (blob, _) = Blob::new(vec![1, 2, 3]);
+        assert!(matches!(
+            DenseResourceElementsAttr::try_from_blob(dynamic, "blob", blob, 4),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        let (blob, _) = Blob::new(vec![1, 2, 3]);
+        let complex = tensor(ComplexType::get(ty.element_type()), &[3]);
+        assert!(matches!(
+            DenseResourceElementsAttr::try_from_blob(complex, "blob", blob, 4),
+            Err(Error::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn unreadable_resources() {
+        let cx = Context::create().unwrap();
+
+        // `index` elements are stored as 64-bit integers, but can't be read as such.
+        let ty = tensor(IndexType::get(&cx), &[2]);
+        let (blob, _) = Blob::new(vec![1, 0, 2, 0]);
+        let attr = DenseResourceElementsAttr::from_blob(ty, "index", blob, 4);
+        assert_eq!(attr.len(), 2);
+        assert!(matches!(
+            attr.values::<i64>(),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            attr.values::<u64>(),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        // Without a resource section, the parsed resource has no blob.
+        let attr = DenseResourceElementsAttr::parse(&cx, "dense_resource<missing> : tensor<3xi32>")
+            .unwrap();
+        assert_eq!(attr.len(), 3);
+        assert!(matches!(
+            attr.values::<i32>(),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn resource_bytecode_round_trip() {
+        let cx = Context::create().unwrap();
+        cx.set_allow_unregistered_dialects(true);
+        let ty = tensor(IntegerType::signless(&cx, 32), &[3]);
+
+        let (blob, _) = Blob::new(vec![7, -8, 9]);
+        let attr = DenseResourceElementsAttr::from_blob(ty, "blob", blob, 4);
+
+        let loc = Location::unknown(&cx);
+        let mut state = OperationState::get("test.op", loc);
+        state.add_attribute("value", attr);
+        let mut module = Module::create_empty(loc);
+        module
+            .body_mut()
+            .append_operation(Operation::create(state).unwrap());
+
+        let mut bytes = Vec::new();
+        module
+            .write_bytecode(&mut bytes, &BytecodeConfig::new())
+            .unwrap();
+        drop(module);
+        drop(cx);
+
+        let cx = Context::create().unwrap();
+        cx.set_allow_unregistered_dialects(true);
+        let module = Module::parse_bytecode(&cx, &bytes).unwrap();
+        let op = module.body().operations().next().unwrap();
+        let attr = op.attribute("value").unwrap();
+        let attr = DenseResourceElementsAttr::try_from(attr).unwrap();
+        assert_eq!(attr.values::<i32>().unwrap(), [7, -8, 9]);
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray() {
+        let cx = Context::create().unwrap();
+        let array = ndarray::arr2(&[[1i64, 2, 3], [4, 5, 6]]);
+
+        let attr = DenseElementsAttr::from_array(IntegerType::signless(&cx, 64).into(), &array);
+        assert_eq!(attr.inner.ty().to_string(), "tensor<2x3xi64>");
+        assert_eq!(attr.to_array::<i64>().unwrap(), array.into_dyn());
+
+        // Transposed arrays are stored in logical order.
+        let transposed = ndarray::arr2(&[[1i64, 2], [3, 4]]).reversed_axes();
+        let attr =
+            DenseElementsAttr::from_array(IntegerType::signless(&cx, 64).into(), &transposed);
+        assert_eq!(attr.values::<i64>().unwrap(), [1, 3, 2, 4]);
+
+        assert!(matches!(
+            DenseElementsAttr::try_from_array(F32Type::get(&cx).into(), &array),
+            Err(Error::TypeMismatch { .. })
+        ));
+    }
+}
@@ -0,0 +1,167 @@
+//! MLIR bytecode serialization.
+//!
+//! Bytecode is a compact binary encoding of MLIR IR which is much faster to load than the textual
+//! form.
+
+use std::{ffi::c_void, io, ptr};
+
+use crate::{ffi, io_callback, Context, Error, IoUserdata, Module, Operation};
+
+/// Options for writing bytecode.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct BytecodeConfig {
+    desired_version: Option<i64>,
+}
+
+impl BytecodeConfig {
+    pub fn new() -> BytecodeConfig {
+        BytecodeConfig::default()
+    }
+
+    /// Emits bytecode of the given version instead of the latest version supported by MLIR.
+    ///
+    /// Writing fails if the IR cannot be represented in that version.
+    pub fn desired_version(mut self, version: i64) -> BytecodeConfig {
+        self.desired_version = Some(version);
+        self
+    }
+}
+
+// The bytecode reader requires its buffer to be aligned to the largest alignment of any resource
+// blob in it, which is rarely more than that of the widest element type.
+const BYTECODE_ALIGNMENT: usize = 64;
+
+#[derive(Copy, Clone)]
+#[repr(C, align(64))]
+struct AlignedChunk([u8; BYTECODE_ALIGNMENT]);
+
+// Owned `MlirBytecodeWriterConfig`.
+struct WriterConfig(ffi::MlirBytecodeWriterConfig);
+
+impl Drop for WriterConfig {
+    fn drop(&mut self) {
+        unsafe { ffi::mlirBytecodeWriterConfigDestroy(self.0) }
+    }
+}
+
+impl Operation<'_> {
+    /// Writes this operation to `w` as bytecode.
+    ///
+    /// If MLIR fails to write the bytecode, the returned error holds the diagnostics it emitted.
+    pub fn write_bytecode<W: io::Write>(
+        &self,
+        w: &mut W,
+        config: &BytecodeConfig,
+    ) -> Result<(), Error> {
+        let raw_config = WriterConfig(unsafe { ffi::mlirBytecodeWriterConfigCreate() });
+        if let Some(version) = config.desired_version {
+            unsafe { ffi::mlirBytecodeWriterConfigDesiredEmitVersion(raw_config.0, version) };
+        }
+
+        let mut userdata = IoUserdata::new(w);
+        let (result, diagnostics) = self.context().with_diagnostics(|| unsafe {
+            ffi::mlirOperationWriteBytecodeWithConfig(
+                self.inner,
+                raw_config.0,
+                Some(io_callback::<W>),
+                &mut userdata as *mut IoUserdata<W> as *mut c_void,
+            )
+        });
+
+        if let Some(e) = userdata.error {
+            return Err(Error::Io(e));
+        }
+
+        // `mlirLogicalResultIsFailure` is a static inline function, so it isn't exported.
+        if result.value == 0 {
+            return Err(Error::Bytecode { diagnostics });
+        }
+
+        Ok(())
+    }
+}
+
+impl<'cx> Module<'cx> {
+    /// Writes the module to `w` as bytecode.
+    ///
+    /// See [`Operation::write_bytecode`].
+    pub fn write_bytecode<W: io::Write>(
+        &self,
+        w: &mut W,
+        config: &BytecodeConfig,
+    ) -> Result<(), Error> {
+        self.as_operation().write_bytecode(w, config)
+    }
+
+    /// Parses a module from bytecode.
+    ///
+    /// Resource blobs in the bytecode must be aligned in memory, so unless `bytes` is already
+    /// aligned to 64 bytes it is copied to a buffer which is. Blobs which require a larger
+    /// alignment fail to parse.
+    ///
+    /// If parsing fails, the returned error holds the diagnostics emitted by the parser.
+    pub fn parse_bytecode(cx: &'cx Context, bytes: &[u8]) -> Result<Module<'cx>, Error> {
+        let aligned: Vec<AlignedChunk>;
+        let data = if bytes.as_ptr().align_offset(BYTECODE_ALIGNMENT) == 0 {
+            bytes.as_ptr()
+        } else {
+            let mut chunks = vec![
+                AlignedChunk([0; BYTECODE_ALIGNMENT]);
+                bytes.len().div_ceil(BYTECODE_ALIGNMENT)
+            ];
+            unsafe {
+                ptr::copy_nonoverlapping(bytes.as_ptr(), chunks.as_mut_ptr().cast(), bytes.len())
+            };
+            aligned = chunks;
+            aligned.as_ptr().cast()
+        };
+
+        let raw = ffi::MlirStringRef {
+            data: data.cast(),
+            length: bytes.len(),
+        };
+
+        // The MLIR parser detects bytecode by its magic number.
+        let (module, diagnostics) = cx.with_diagnostics(|| unsafe {
+            Module::from_raw(ffi::mlirModuleCreateParse(cx.as_raw(), raw))
+        });
+
+        module.ok_or(Error::Parse { diagnostics })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESOURCE: &str = r#"
+        module attributes {test.blob = dense_resource<blob> : tensor<2xi32>} {}
+
+        {-#
+          dialect_resources: {
+            builtin: {
+              blob: "0x040000000100000002000000"
+            }
+          }
+        #-}
+    "#;
+
+    #[test]
+    fn parse_unaligned_bytecode() {
+        let cx = Context::create().unwrap();
+        cx.set_allow_unregistered_dialects(true);
+        let module = Module::create_parse(&cx, RESOURCE).unwrap();
+
+        let mut bytes = vec![0];
+        module
+            .write_bytecode(&mut bytes, &BytecodeConfig::new())
+            .unwrap();
+
+        // Offset the bytecode by one byte so that the blob is misaligned in `bytes`.
+        let parsed = Module::parse_bytecode(&cx, &bytes[1..]).unwrap();
+        assert_eq!(
+            parsed.as_operation().to_string(),
+            module.as_operation().to_string()
+        );
+    }
+}
@@ -0,0 +1,354 @@
+//! Conversions between Rust values and MLIR types and attributes.
+//!
+//! [`IntoMlirType`] maps a Rust type to the MLIR type of its values, e.g. `u8` to `ui8`.
+//! [`IntoAttribute`] and [`TryFromAttribute`] convert values to and from attributes of that type.
+
+use std::{collections::HashMap, hash::BuildHasher};
+
+use crate::{
+    attr::{
+        ArrayAttr, AttributeSubtype, BoolAttr, DictionaryAttr, FloatAttr, IntegerAttr, StringAttr,
+    },
+    ty::{Dim, F32Type, F64Type, IntegerType, RankedTensorType},
+    Attribute, Context, Error, NamedAttribute, Type,
+};
+
+/// A Rust type with a corresponding MLIR type.
+pub trait IntoMlirType {
+    /// Returns the MLIR type corresponding to `Self`.
+    fn mlir_type(cx: &Context) -> Type<'_>;
+
+    // Nested arrays are flattened into a single tensor type, so arrays report their dimensions
+    // and scalar element type separately.
+
+    #[doc(hidden)]
+    fn push_dims(_dims: &mut Vec<Dim>) {}
+
+    #[doc(hidden)]
+    fn element_type(cx: &Context) -> Type<'_> {
+        Self::mlir_type(cx)
+    }
+}
+
+macro_rules! int_types {
+    ($($t:ty => $ctor:ident($width:literal);)*) => {
+        $(
+            impl IntoMlirType for $t {
+                fn mlir_type(cx: &Context) -> Type<'_> {
+                    IntegerType::$ctor(cx, $width).into()
+                }
+            }
+
+            impl<'cx> IntoAttribute<'cx> for $t {
+                fn into_attribute(self, cx: &'cx Context) -> Attribute<'cx> {
+                    IntegerAttr::new(<$t>::mlir_type(cx), self as i64).into()
+                }
+            }
+
+            impl<'cx> TryFromAttribute<'cx> for $t {
+                fn try_from_attribute(attr: Attribute<'cx>) -> Result<$t, Error> {
+                    let int = IntegerAttr::try_from(attr)?;
+                    let value = int
+                        .value()
+                        .or(int.signed_value())
+                        .map(i128::from)
+                        .or(int.unsigned_value().map(i128::from))
+                        .ok_or_else(|| Error::TypeMismatch {
+                            expected: "integer attribute of at most 64 bits",
+                            found: attr.to_string(),
+                        })?;
+
+                    <$t>::try_from(value).map_err(|_| Error::OutOfRange {
+                        value: value.to_string(),
+                        ty: stringify!($t).into(),
+                    })
+                }
+            }
+        )*
+    };
+}
+
+int_types! {
+    i8 => signless(8);
+    i16 => signless(16);
+    i32 => signless(32);
+    i64 => signless(64);
+    u8 => unsigned(8);
+    u16 => unsigned(16);
+    u32 => unsigned(32);
+    u64 => unsigned(64);
+}
+
+macro_rules! float_types {
+    ($($t:ty => $ty:ident($is_fn:ident);)*) => {
+        $(
+            impl IntoMlirType for $t {
+                fn mlir_type(cx: &Context) -> Type<'_> {
+                    $ty::get(cx).into()
+                }
+            }
+
+            impl<'cx> IntoAttribute<'cx> for $t {
+                fn into_attribute(self, cx: &'cx Context) -> Attribute<'cx> {
+                    FloatAttr::new(<$t>::mlir_type(cx), self as f64).into()
+                }
+            }
+
+            impl<'cx> TryFromAttribute<'cx> for $t {
+                fn try_from_attribute(attr: Attribute<'cx>) -> Result<$t, Error> {
+                    let float = FloatAttr::try_from(attr)?;
+
+                    // Converting from other float types could silently lose precision.
+                    if !attr.ty().$is_fn() {
+                        return Err(Error::TypeMismatch {
+                            expected: concat!(stringify!($t), " attribute"),
+                            found: attr.to_string(),
+                        });
+                    }
+
+                    Ok(float.value() as $t)
+                }
+            }
+        )*
+    };
+}
+
+float_types! {
+    f32 => F32Type(is_f32);
+    f64 => F64Type(is_f64);
+}
+
+impl IntoMlirType for bool {
+    fn mlir_type(cx: &Context) -> Type<'_> {
+        IntegerType::signless(cx, 1).into()
+    }
+}
+
+/// Arrays correspond to ranked tensors, e.g. `[[i32; 3]; 2]` to `tensor<2x3xi32>`.
+impl<T: IntoMlirType, const N: usize> IntoMlirType for [T; N] {
+    fn mlir_type(cx: &Context) -> Type<'_> {
+        let mut dims = Vec::new();
+        Self::push_dims(&mut dims);
+        RankedTensorType::get(Self::element_type(cx), &dims, None).into()
+    }
+
+    fn push_dims(dims: &mut Vec<Dim>) {
+        dims.push(Dim::Static(N as u64));
+        T::push_dims(dims);
+    }
+
+    fn element_type(cx: &Context) -> Type<'_> {
+        T::element_type(cx)
+    }
+}
+
+/// A value which can be converted to an attribute.
+pub trait IntoAttribute<'cx> {
+    fn into_attribute(self, cx: &'cx Context) -> Attribute<'cx>;
+}
+
+/// A value which can be converted from an attribute.
+pub trait TryFromAttribute<'cx>: Sized {
+    /// Converts `attr` to a value of this type.
+    ///
+    /// Returns an error if `attr` is not of the expected kind, or if its value does not fit in
+    /// this type.
+    fn try_from_attribute(attr: Attribute<'cx>) -> Result<Self, Error>;
+}
+
+impl<'cx, A: Into<Attribute<'cx>>> IntoAttribute<'cx> for A {
+    fn into_attribute(self, _cx: &'cx Context) -> Attribute<'cx> {
+        self.into()
+    }
+}
+
+impl<'cx, A: AttributeSubtype<'cx>> TryFromAttribute<'cx> for A {
+    fn try_from_attribute(attr: Attribute<'cx>) -> Result<A, Error> {
+        attr.downcast()
+            .map_err(|attr: Attribute<'cx>| Error::TypeMismatch {
+                expected: std::any::type_name::<A>(),
+                found: attr.to_string(),
+            })
+    }
+}
+
+impl<'cx> TryFromAttribute<'cx> for Attribute<'cx> {
+    fn try_from_attribute(attr: Attribute<'cx>) -> Result<Attribute<'cx>, Error> {
+        Ok(attr)
+    }
+}
+
+impl<'cx> IntoAttribute<'cx> for bool {
+    fn into_attribute(self, cx: &'cx Context) -> Attribute<'cx> {
+        BoolAttr::new(cx, self).into()
+    }
+}
+
+impl<'cx> TryFromAttribute<'cx> for bool {
+    fn try_from_attribute(attr: Attribute<'cx>) -> Result<bool, Error> {
+        Ok(BoolAttr::try_from(attr)?.value())
+    }
+}
+
+impl<'cx> IntoAttribute<'cx> for &str {
+    fn into_attribute(self, cx: &'cx Context) -> Attribute<'cx> {
+        StringAttr::new(cx, self).into()
+    }
+}
+
+impl<'cx> IntoAttribute<'cx> for String {
+    fn into_attribute(self, cx: &'cx Context) -> Attribute<'cx> {
+        self.as_str().into_attribute(cx)
+    }
+}
+
+impl<'cx> TryFromAttribute<'cx> for String {
+    fn try_from_attribute(attr: Attribute<'cx>) -> Result<String, Error> {
+        Ok(StringAttr::try_from(attr)?.value().to_str()?.to_owned())
+    }
+}
+
+/// Vectors are converted to [`ArrayAttr`]s.
+impl<'cx, T: IntoAttribute<'cx>> IntoAttribute<'cx> for Vec<T> {
+    fn into_attribute(self, cx: &'cx Context) -> Attribute<'cx> {
+        ArrayAttr::from_iter_in(cx, self.into_iter().map(|e| e.into_attribute(cx))).into()
+    }
+}
+
+impl<'cx, T: TryFromAttribute<'cx>> TryFromAttribute<'cx> for Vec<T> {
+    fn try_from_attribute(attr: Attribute<'cx>) -> Result<Vec<T>, Error> {
+        ArrayAttr::try_from(attr)?
+            .iter()
+            .map(T::try_from_attribute)
+            .collect()
+    }
+}
+
+/// Maps are converted to [`DictionaryAttr`]s, whose entries MLIR sorts by name.
+impl<'cx, T, S> IntoAttribute<'cx> for HashMap<String, T, S>
+where
+    T: IntoAttribute<'cx>,
+{
+    fn into_attribute(self, cx: &'cx Context) -> Attribute<'cx> {
+        let elements = self
+            .into_iter()
+            .map(|(name, value)| NamedAttribute::get(name.as_str(), value.into_attribute(cx)));
+        DictionaryAttr::from_iter_in(cx, elements).into()
+    }
+}
+
+impl<'cx, T, S> TryFromAttribute<'cx> for HashMap<String, T, S>
+where
+    T: TryFromAttribute<'cx>,
+    S: BuildHasher + Default,
+{
+    fn try_from_attribute(attr: Attribute<'cx>) -> Result<HashMap<String, T, S>, Error> {
+        DictionaryAttr::try_from(attr)?
+            .iter()
+            .map(|named| {
+                let name = named.name.value().to_str()?.to_owned();
+                Ok((name, T::try_from_attribute(named.attribute)?))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<'cx, T>(cx: &'cx Context, value: T) -> T
+    where
+        T: IntoAttribute<'cx> + TryFromAttribute<'cx>,
+    {
+        T::try_from_attribute(value.into_attribute(cx)).unwrap()
+    }
+
+    #[test]
+    fn ints() {
+        let cx = Context::create().unwrap();
+        assert_eq!(round_trip(&cx, i8::MIN), i8::MIN);
+        assert_eq!(round_trip(&cx, -1i32), -1);
+        assert_eq!(round_trip(&cx, i64::MAX), i64::MAX);
+        assert_eq!(round_trip(&cx, u8::MAX), u8::MAX);
+        assert_eq!(round_trip(&cx, u64::MAX), u64::MAX);
+        assert_eq!(7u16.into_attribute(&cx).to_string(), "7 : ui16");
+
+        let signed = IntegerAttr::new(IntegerType::signed(&cx, 32).into(), -5).into();
+        assert_eq!(i32::try_from_attribute(signed).unwrap(), -5);
+        assert!(matches!(
+            u32::try_from_attribute(signed),
+            Err(Error::OutOfRange { .. })
+        ));
+
+        let wide = IntegerAttr::new(IntegerType::signless(&cx, 128).into(), 1).into();
+        assert!(matches!(
+            i64::try_from_attribute(wide),
+            Err(Error::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            i8::try_from_attribute(1000i16.into_attribute(&cx)),
+            Err(Error::OutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn floats() {
+        let cx = Context::create().unwrap();
+        assert_eq!(round_trip(&cx, 0.1f32), 0.1);
+        assert_eq!(round_trip(&cx, 0.1f64), 0.1);
+        assert!(matches!(
+            f32::try_from_attribute(0.1f64.into_attribute(&cx)),
+            Err(Error::TypeMismatch { .. })
+        ));
+        assert!(matches!(
+            f64::try_from_attribute(0.5f32.into_attribute(&cx)),
+            Err(Error::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn bools() {
+        let cx = Context::create().unwrap();
+        assert!(round_trip(&cx, true));
+        assert!(!round_trip(&cx, false));
+        assert!(matches!(
+            bool::try_from_attribute(1i8.into_attribute(&cx)),
+            Err(Error::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn strings() {
+        let cx = Context::create().unwrap();
+        let attr = "hello".into_attribute(&cx);
+        assert_eq!(attr.to_string(), "\"hello\"");
+        assert_eq!(String::try_from_attribute(attr).unwrap(), "hello");
+        assert_eq!(round_trip(&cx, String::from("world")), "world");
+    }
+
+    #[test]
+    fn vecs() {
+        let cx = Context::create().unwrap();
+        let attr = vec![1i32, 2, 3].into_attribute(&cx);
+        assert!(ArrayAttr::try_from(attr).is_ok());
+        assert_eq!(Vec::<i32>::try_from_attribute(attr).unwrap(), [1, 2, 3]);
+        assert!(round_trip(&cx, Vec::<i32>::new()).is_empty());
+        assert!(matches!(
+            Vec::<String>::try_from_attribute(attr),
+            Err(Error::TypeMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn hash_maps() {
+        let cx = Context::create().unwrap();
+        let map: HashMap<String, i64> = [("b".to_owned(), 2), ("a".to_owned(), 1)].into();
+        let attr = map.clone().into_attribute(&cx);
+        assert_eq!(attr.to_string(), "{a = 1 : i64, b = 2 : i64}");
+        assert_eq!(
+            HashMap::<String, i64>::try_from_attribute(attr).unwrap(),
+            map
+        );
+    }
+}
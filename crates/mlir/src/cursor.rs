@@ -1,52 +1,104 @@
-use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
 
-use crate::{ffi, Block, BlockRef, RegionRef};
+use crate::{ffi, is_detachable, Block, BlockMut, BlockRef, Operation, OperationRef, RegionMut};
+
+// The C API only links blocks and operations to their successor. Cursors follow those links, and
+// remember the elements they moved past on a stack so that moving back is usually a pop. The stack
+// holds consecutive elements ending at the predecessor of the current element, but may not reach
+// back to the first one, e.g. after wrapping around through the null element; when it runs out,
+// the cursor walks the container from the start to refill it. Cursors borrow the container
+// mutably, so it cannot change behind their back.
 
 // NOTE: Deliberately not Send/Sync.
-pub struct BlockCursor<'region> {
-    region: RegionRef<'region>,
-    block: ffi::MlirBlock,
+pub struct BlockCursor<'cx, 'region> {
+    region: RegionMut<'cx, 'region>,
+    // The block the cursor is pointing to, which is null for the null element.
+    current: ffi::MlirBlock,
+    prev: Vec<ffi::MlirBlock>,
 }
 
-impl<'region> BlockCursor<'region> {
+impl<'cx, 'region> BlockCursor<'cx, 'region> {
+    /// Creates a cursor pointing to the first block in `region`.
+    ///
+    /// If the region is empty, the cursor points to the null element.
+    pub fn front(region: RegionMut<'cx, 'region>) -> BlockCursor<'cx, 'region> {
+        BlockCursor {
+            current: unsafe { ffi::mlirRegionGetFirstBlock(region.as_raw()) },
+            region,
+            prev: Vec::new(),
+        }
+    }
+
+    /// Creates a cursor pointing to the last block in `region`.
+    ///
+    /// If the region is empty, the cursor points to the null element.
+    pub fn back(region: RegionMut<'cx, 'region>) -> BlockCursor<'cx, 'region> {
+        let mut cursor = BlockCursor {
+            region,
+            current: ffi::MlirBlock {
+                ptr: std::ptr::null_mut(),
+            },
+            prev: Vec::new(),
+        };
+        cursor.move_prev();
+        cursor
+    }
+
     /// Returns a reference to the block the cursor is pointing to.
     ///
     /// If the cursor is pointing to the null element, returns `None`.
-    pub fn get(&self) -> Option<BlockRef> {
-        unsafe { BlockRef::from_raw(self.block) }
+    pub fn get(&self) -> Option<BlockRef<'cx, '_>> {
+        unsafe { BlockRef::from_raw(self.current) }
     }
 
     /// Detaches and returns the block the cursor is pointing to.
     ///
-    /// If the cursor is pointing to the null element, returns `None`.
-    pub fn detach(&mut self) -> Option<Block> {
-        let to_detach = self.block;
-        if to_detach.ptr.is_null() {
+    /// The cursor is moved to the next block. If the cursor is pointing to the null element, or
+    /// to the body of a `builtin.module`, returns `None` and the cursor is not moved.
+    ///
+    /// See [`BlockMut::detach`].
+    pub fn detach(&mut self) -> Option<Block<'cx>> {
+        let to_detach = self.current;
+        if to_detach.ptr.is_null() || !unsafe { is_detachable(to_detach) } {
             return None;
         }
 
-        self.move_next();
+        // The predecessor of the detached block becomes the predecessor of the next one.
         unsafe {
+            self.current = ffi::mlirBlockGetNextInRegion(to_detach);
             ffi::mlirBlockDetach(to_detach);
-            Some(Block::from_raw(to_detach).unwrap())
+            Block::from_raw(to_detach)
         }
     }
 
     /// Inserts a block before the block the cursor is pointing to.
     ///
     /// If the cursor is pointing to the null element, appends the block to the region.
-    pub fn insert_before(&mut self, block: Block) {
+    pub fn insert_before(&mut self, block: Block<'cx>) {
+        // The region takes ownership of the block.
+        let block = ManuallyDrop::new(block);
+
         unsafe {
-            ffi::mlirRegionInsertOwnedBlockBefore(self.region.as_raw(), self.block, block.as_raw())
+            ffi::mlirRegionInsertOwnedBlockBefore(
+                self.region.as_raw(),
+                self.current,
+                block.as_raw(),
+            )
         };
+
+        self.prev.push(block.as_raw());
     }
 
     /// Inserts a block after the block the cursor is pointing to.
     ///
     /// If the cursor is pointing to the null element, prepends the block to the region.
-    pub fn insert_after(&mut self, block: Block) {
+    pub fn insert_after(&mut self, block: Block<'cx>) {
+        // The region takes ownership of the block.
+        let block = ManuallyDrop::new(block);
+
+        // Prepending leaves the last block, and thus the stack, unchanged.
         unsafe {
-            ffi::mlirRegionInsertOwnedBlockAfter(self.region.as_raw(), self.block, block.as_raw())
+            ffi::mlirRegionInsertOwnedBlockAfter(self.region.as_raw(), self.current, block.as_raw())
         };
     }
 
@@ -55,11 +107,280 @@ impl<'region> BlockCursor<'region> {
     /// If the cursor was pointing to the null element, calling this method points it to the first
     /// block in the region.
     pub fn move_next(&mut self) {
-        self.block = unsafe { ffi::mlirBlockGetNextInRegion(self.block) };
+        if self.current.ptr.is_null() {
+            self.prev.clear();
+            self.current = unsafe { ffi::mlirRegionGetFirstBlock(self.region.as_raw()) };
+        } else {
+            self.prev.push(self.current);
+            self.current = unsafe { ffi::mlirBlockGetNextInRegion(self.current) };
+        }
+    }
+
+    /// Points the cursor to the previous block in the region.
+    ///
+    /// If the cursor was pointing to the null element, calling this method points it to the last
+    /// block in the region.
+    pub fn move_prev(&mut self) {
+        if self.prev.is_empty() {
+            let mut block = unsafe { ffi::mlirRegionGetFirstBlock(self.region.as_raw()) };
+            while block.ptr != self.current.ptr {
+                self.prev.push(block);
+                block = unsafe { ffi::mlirBlockGetNextInRegion(block) };
+            }
+        }
+
+        // Moving back from the first block wraps around to the null element.
+        self.current = self.prev.pop().unwrap_or(ffi::MlirBlock {
+            ptr: std::ptr::null_mut(),
+        });
+    }
+}
+
+// NOTE: Deliberately not Send/Sync.
+pub struct OperationCursor<'cx, 'block> {
+    block: BlockMut<'cx, 'block>,
+    // The operation the cursor is pointing to, which is null for the null element.
+    current: ffi::MlirOperation,
+    prev: Vec<ffi::MlirOperation>,
+}
+
+impl<'cx, 'block> OperationCursor<'cx, 'block> {
+    /// Creates a cursor pointing to the first operation in `block`.
+    ///
+    /// If the block is empty, the cursor points to the null element.
+    pub fn front(block: BlockMut<'cx, 'block>) -> OperationCursor<'cx, 'block> {
+        OperationCursor {
+            current: unsafe { ffi::mlirBlockGetFirstOperation(block.as_raw()) },
+            block,
+            prev: Vec::new(),
+        }
+    }
+
+    /// Creates a cursor pointing to the last operation in `block`.
+    ///
+    /// If the block is empty, the cursor points to the null element.
+    pub fn back(block: BlockMut<'cx, 'block>) -> OperationCursor<'cx, 'block> {
+        let mut cursor = OperationCursor {
+            block,
+            current: ffi::MlirOperation {
+                ptr: std::ptr::null_mut(),
+            },
+            prev: Vec::new(),
+        };
+        cursor.move_prev();
+        cursor
+    }
+
+    /// Returns a reference to the operation the cursor is pointing to.
+    ///
+    /// If the cursor is pointing to the null element, returns `None`.
+    pub fn get(&self) -> Option<OperationRef<'cx, '_>> {
+        unsafe { OperationRef::from_raw(self.current) }
+    }
+
+    /// Detaches and returns the operation the cursor is pointing to.
+    ///
+    /// The cursor is moved to the next operation. If the cursor is pointing to the null element,
+    /// returns `None`.
+    ///
+    /// See [`OperationMut::detach`](crate::OperationMut::detach).
+    pub fn detach(&mut self) -> Option<Operation<'cx>> {
+        let to_detach = self.current;
+        if to_detach.ptr.is_null() {
+            return None;
+        }
+
+        // The predecessor of the detached operation becomes the predecessor of the next one.
+        unsafe {
+            self.current = ffi::mlirOperationGetNextInBlock(to_detach);
+            ffi::mlirOperationRemoveFromParent(to_detach);
+            Operation::from_raw(to_detach)
+        }
+    }
+
+    /// Inserts an operation before the operation the cursor is pointing to.
+    ///
+    /// If the cursor is pointing to the null element, appends the operation to the block.
+    pub fn insert_before(&mut self, op: Operation<'cx>) {
+        // The block takes ownership of the operation.
+        let op = ManuallyDrop::new(op);
+
+        unsafe {
+            ffi::mlirBlockInsertOwnedOperationBefore(self.block.as_raw(), self.current, op.as_raw())
+        };
+
+        self.prev.push(op.as_raw());
+    }
+
+    /// Inserts an operation after the operation the cursor is pointing to.
+    ///
+    /// If the cursor is pointing to the null element, prepends the operation to the block.
+    pub fn insert_after(&mut self, op: Operation<'cx>) {
+        // The block takes ownership of the operation.
+        let op = ManuallyDrop::new(op);
+
+        // Prepending leaves the last operation, and thus the stack, unchanged.
+        unsafe {
+            ffi::mlirBlockInsertOwnedOperationAfter(self.block.as_raw(), self.current, op.as_raw())
+        };
+    }
+
+    /// Points the cursor to the next operation in the block.
+    ///
+    /// If the cursor was pointing to the null element, calling this method points it to the first
+    /// operation in the block.
+    pub fn move_next(&mut self) {
+        if self.current.ptr.is_null() {
+            self.prev.clear();
+            self.current = unsafe { ffi::mlirBlockGetFirstOperation(self.block.as_raw()) };
+        } else {
+            self.prev.push(self.current);
+            self.current = unsafe { ffi::mlirOperationGetNextInBlock(self.current) };
+        }
+    }
+
+    /// Points the cursor to the previous operation in the block.
+    ///
+    /// If the cursor was pointing to the null element, calling this method points it to the last
+    /// operation in the block.
+    pub fn move_prev(&mut self) {
+        if self.prev.is_empty() {
+            let mut op = unsafe { ffi::mlirBlockGetFirstOperation(self.block.as_raw()) };
+            while op.ptr != self.current.ptr {
+                self.prev.push(op);
+                op = unsafe { ffi::mlirOperationGetNextInBlock(op) };
+            }
+        }
+
+        // Moving back from the first operation wraps around to the null element.
+        self.current = self.prev.pop().unwrap_or(ffi::MlirOperation {
+            ptr: std::ptr::null_mut(),
+        });
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Context, Location, OperationState, Region};
 
-    // TODO: not exposed by the C API.
-    // pub fn move_prev(&mut self) {
-    //     self.block = unsafe { ffi::mlirBlockGetPrevInRegion(self.block) };
-    // }
+    fn op<'cx>(cx: &'cx Context, name: &str) -> Operation<'cx> {
+        Operation::create(OperationState::get(name, Location::unknown(cx))).unwrap()
+    }
+
+    fn names(cursor: &OperationCursor) -> Vec<String> {
+        cursor
+            .block
+            .operations()
+            .map(|op| op.name().value().to_str().unwrap().to_owned())
+            .collect()
+    }
+
+    fn current(cursor: &OperationCursor) -> Option<String> {
+        cursor
+            .get()
+            .map(|op| op.name().value().to_str().unwrap().to_owned())
+    }
+
+    #[test]
+    fn operation_cursor() {
+        let cx = Context::create().unwrap();
+        cx.set_allow_unregistered_dialects(true);
+        let mut block = Block::create();
+
+        let mut cursor = OperationCursor::front(block.by_mut());
+        assert!(cursor.get().is_none());
+        cursor.insert_before(op(&cx, "test.b"));
+        cursor.insert_after(op(&cx, "test.a"));
+        cursor.insert_before(op(&cx, "test.d"));
+        assert_eq!(names(&cursor), ["test.a", "test.b", "test.d"]);
+
+        cursor.move_prev();
+        assert_eq!(current(&cursor).as_deref(), Some("test.d"));
+        cursor.insert_before(op(&cx, "test.c"));
+        assert_eq!(current(&cursor).as_deref(), Some("test.d"));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(current(&cursor).as_deref(), Some("test.b"));
+        cursor.insert_after(op(&cx, "test.bb"));
+        assert_eq!(
+            names(&cursor),
+            ["test.a", "test.b", "test.bb", "test.c", "test.d"]
+        );
+
+        let detached = cursor.detach().unwrap();
+        assert_eq!(detached.name().value().to_str().unwrap(), "test.b");
+        assert_eq!(current(&cursor).as_deref(), Some("test.bb"));
+
+        cursor.move_prev();
+        cursor.move_prev();
+        assert!(cursor.get().is_none());
+        assert!(cursor.detach().is_none());
+        cursor.move_prev();
+        assert_eq!(current(&cursor).as_deref(), Some("test.d"));
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(current(&cursor).as_deref(), Some("test.a"));
+
+        let cursor = OperationCursor::back(block.by_mut());
+        assert_eq!(current(&cursor).as_deref(), Some("test.d"));
+    }
+
+    #[test]
+    fn rewrite_in_place() {
+        let cx = Context::create().unwrap();
+        cx.set_allow_unregistered_dialects(true);
+        let mut block = Block::create();
+        for name in ["test.a", "test.b", "test.c"] {
+            block.by_mut().append_operation(op(&cx, name));
+        }
+
+        // Replace `test.b` with two operations while walking forwards.
+        let mut cursor = OperationCursor::front(block.by_mut());
+        while let Some(name) = current(&cursor) {
+            if name == "test.b" {
+                drop(cursor.detach());
+                cursor.insert_before(op(&cx, "test.x"));
+                cursor.insert_before(op(&cx, "test.y"));
+            } else {
+                cursor.move_next();
+            }
+        }
+        assert_eq!(names(&cursor), ["test.a", "test.x", "test.y", "test.c"]);
+
+        // Walk backwards through the inserted operations.
+        let mut visited = Vec::new();
+        cursor.move_prev();
+        while let Some(name) = current(&cursor) {
+            visited.push(name);
+            cursor.move_prev();
+        }
+        assert_eq!(visited, ["test.c", "test.y", "test.x", "test.a"]);
+    }
+
+    #[test]
+    fn block_cursor() {
+        let mut region = Region::create();
+        let (a, b) = (Block::create(), Block::create());
+        let (a_raw, b_raw) = (a.as_raw(), b.as_raw());
+
+        let mut cursor = BlockCursor::back(region.by_mut());
+        assert!(cursor.get().is_none());
+        cursor.insert_before(b);
+        cursor.insert_after(a);
+        cursor.move_next();
+        assert_eq!(cursor.get().unwrap().as_raw().ptr, a_raw.ptr);
+        cursor.move_next();
+        assert_eq!(cursor.get().unwrap().as_raw().ptr, b_raw.ptr);
+
+        let detached = cursor.detach().unwrap();
+        assert_eq!(detached.as_raw().ptr, b_raw.ptr);
+        assert!(cursor.get().is_none());
+        cursor.move_prev();
+        assert_eq!(cursor.get().unwrap().as_raw().ptr, a_raw.ptr);
+        cursor.move_prev();
+        assert!(cursor.get().is_none());
+
+        assert_eq!(region.blocks().count(), 1);
+    }
 }
@@ -0,0 +1,235 @@
+//! MLIR diagnostics.
+//!
+//! By default, MLIR prints diagnostics to stderr. [`Context::with_diagnostics`] instead captures
+//! the diagnostics emitted during a closure as [`Diagnostic`]s.
+
+use std::{
+    cell::RefCell,
+    ffi::c_void,
+    fmt::{self, Formatter},
+    panic::{self, AssertUnwindSafe},
+    slice,
+};
+
+use crate::{ffi, Context, Location};
+
+/// The severity of a diagnostic.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+    Remark,
+}
+
+impl Severity {
+    fn from_raw(raw: ffi::MlirDiagnosticSeverity) -> Severity {
+        match raw {
+            ffi::MlirDiagnosticSeverity_MlirDiagnosticError => Severity::Error,
+            ffi::MlirDiagnosticSeverity_MlirDiagnosticWarning => Severity::Warning,
+            ffi::MlirDiagnosticSeverity_MlirDiagnosticNote => Severity::Note,
+            ffi::MlirDiagnosticSeverity_MlirDiagnosticRemark => Severity::Remark,
+            // This is called from a diagnostic handler, which must not panic. Treat severities
+            // added by newer versions of MLIR as errors, so that they aren't silently ignored.
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+            Severity::Remark => "remark",
+        })
+    }
+}
+
+/// A diagnostic emitted by MLIR.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The location the diagnostic refers to, as printed by MLIR.
+    pub location: String,
+    pub message: String,
+    /// Notes attached to the diagnostic.
+    pub notes: Vec<Diagnostic>,
+}
+
+impl Diagnostic {
+    /// Converts an MLIR diagnostic, which is only valid for the duration of a handler call.
+    unsafe fn from_raw(raw: ffi::MlirDiagnostic) -> Diagnostic {
+        let severity = Severity::from_raw(unsafe { ffi::mlirDiagnosticGetSeverity(raw) });
+
+        let location = unsafe { Location::from_raw(ffi::mlirDiagnosticGetLocation(raw)) }
+            .map(|loc| loc.to_string())
+            .unwrap_or_default();
+
+        let mut message = Vec::new();
+        unsafe {
+            ffi::mlirDiagnosticPrint(
+                raw,
+                Some(bytes_callback),
+                &mut message as *mut Vec<u8> as *mut c_void,
+            );
+        }
+
+        let num_notes = unsafe { ffi::mlirDiagnosticGetNumNotes(raw) };
+        let notes = (0..num_notes)
+            .map(|i| unsafe { Diagnostic::from_raw(ffi::mlirDiagnosticGetNote(raw, i)) })
+            .collect();
+
+        Diagnostic {
+            severity,
+            location,
+            message: String::from_utf8_lossy(&message).into_owned(),
+            notes,
+        }
+    }
+
+    /// Returns `true` if this diagnostic is an error.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.location, self.severity, self.message)?;
+
+        for note in &self.notes {
+            write!(f, "\n{note}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// MLIR string callback for appending to a `Vec<u8>`.
+unsafe extern "C" fn bytes_callback(s: ffi::MlirStringRef, userdata: *mut c_void) {
+    let buf = unsafe { &mut *(userdata as *mut Vec<u8>) };
+    buf.extend_from_slice(unsafe { slice::from_raw_parts(s.data as *const u8, s.length) });
+}
+
+/// MLIR diagnostic handler which appends to a `RefCell<Vec<Diagnostic>>`.
+unsafe extern "C" fn diagnostic_handler(
+    diag: ffi::MlirDiagnostic,
+    userdata: *mut c_void,
+) -> ffi::MlirLogicalResult {
+    // Unwinding out of an `extern "C"` function aborts, so a panic while converting the
+    // diagnostic leaves it unhandled instead, and MLIR prints it.
+    let handled = panic::catch_unwind(AssertUnwindSafe(|| {
+        let diagnostics = unsafe { &*(userdata as *const RefCell<Vec<Diagnostic>>) };
+        let diagnostic = unsafe { Diagnostic::from_raw(diag) };
+        diagnostics.borrow_mut().push(diagnostic);
+    }))
+    .is_ok();
+
+    // Mark the diagnostic as handled so that it isn't printed.
+    ffi::MlirLogicalResult {
+        value: handled as i8,
+    }
+}
+
+// Detaches a diagnostic handler when dropped, even if the scoped closure panics.
+struct HandlerGuard<'a> {
+    context: &'a Context,
+    id: ffi::MlirDiagnosticHandlerID,
+}
+
+impl Drop for HandlerGuard<'_> {
+    fn drop(&mut self) {
+        unsafe { ffi::mlirContextDetachDiagnosticHandler(self.context.inner, self.id) }
+    }
+}
+
+impl Context {
+    /// Calls `f`, capturing the diagnostics emitted in this context while it runs.
+    ///
+    /// Captured diagnostics are not printed to stderr. Calls may be nested, in which case each
+    /// diagnostic is captured by the innermost call.
+    pub fn with_diagnostics<R, F>(&self, f: F) -> (R, Vec<Diagnostic>)
+    where
+        F: FnOnce() -> R,
+    {
+        let diagnostics = RefCell::new(Vec::new());
+
+        let value = {
+            let _guard = HandlerGuard {
+                context: self,
+                id: unsafe {
+                    ffi::mlirContextAttachDiagnosticHandler(
+                        self.inner,
+                        Some(diagnostic_handler),
+                        &diagnostics as *const RefCell<Vec<Diagnostic>> as *mut c_void,
+                        None,
+                    )
+                },
+            };
+
+            f()
+        };
+
+        (value, diagnostics.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use super::*;
+    use crate::{Error, Type};
+
+    #[test]
+    fn unknown_severity() {
+        assert_eq!(Severity::from_raw(1234), Severity::Error);
+    }
+
+    #[test]
+    fn capture_parse_error() {
+        let cx = Context::create().unwrap();
+
+        let (ty, diagnostics) = cx.with_diagnostics(|| Type::parse(&cx, "i32"));
+        assert!(ty.is_ok());
+        assert!(diagnostics.is_empty());
+
+        let Err(Error::Parse { diagnostics }) = Type::parse(&cx, "!!") else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].is_error());
+        assert!(!diagnostics[0].message.is_empty());
+    }
+
+    #[test]
+    fn nested_capture() {
+        let cx = Context::create().unwrap();
+        let loc = Location::unknown(&cx);
+        let emit = |msg: &str| {
+            let msg = CString::new(msg).unwrap();
+            unsafe { ffi::mlirEmitError(loc.as_raw(), msg.as_ptr()) }
+        };
+
+        let (inner_diagnostics, outer_diagnostics) = cx.with_diagnostics(|| {
+            emit("outer before");
+            let ((), inner_diagnostics) = cx.with_diagnostics(|| emit("inner"));
+            emit("outer after");
+            inner_diagnostics
+        });
+
+        let messages = |diagnostics: &[Diagnostic]| {
+            diagnostics
+                .iter()
+                .map(|d| d.message.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(messages(&inner_diagnostics), ["inner"]);
+        assert_eq!(
+            messages(&outer_diagnostics),
+            ["outer before", "outer after"]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use std::{fmt, io, str::Utf8Error};
+
+use crate::diag::Diagnostic;
+
+/// An error returned by the MLIR bindings.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Parsing failed.
+    Parse { diagnostics: Vec<Diagnostic> },
+    /// Verification failed.
+    Verify { diagnostics: Vec<Diagnostic> },
+    /// MLIR returned a null handle where an object was expected.
+    NullHandle {
+        /// The kind of object that was expected.
+        kind: &'static str,
+        diagnostics: Vec<Diagnostic>,
+    },
+    /// Writing bytecode failed.
+    Bytecode { diagnostics: Vec<Diagnostic> },
+    /// An I/O error occurred.
+    Io(io::Error),
+    /// A string was not valid UTF-8.
+    InvalidUtf8(Utf8Error),
+    /// An object did not have the expected type.
+    TypeMismatch {
+        expected: &'static str,
+        /// The object, as printed by MLIR.
+        found: String,
+    },
+    /// An integer value did not fit in the type it was converted to.
+    OutOfRange {
+        /// The value, in decimal.
+        value: String,
+        /// The name of the type, e.g. `u8` or as printed by MLIR.
+        ty: String,
+    },
+    /// An argument was invalid, e.g. two slices which should have the same length did not.
+    InvalidArgument(String),
+}
+
+impl Error {
+    pub(crate) fn null_handle(kind: &'static str) -> Error {
+        Error::NullHandle {
+            kind,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Returns the diagnostics emitted by MLIR while the error occurred.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        match self {
+            Error::Parse { diagnostics }
+            | Error::Verify { diagnostics }
+            | Error::NullHandle { diagnostics, .. }
+            | Error::Bytecode { diagnostics } => diagnostics,
+            Error::Io(_)
+            | Error::InvalidUtf8(_)
+            | Error::TypeMismatch { .. }
+            | Error::OutOfRange { .. }
+            | Error::InvalidArgument(_) => &[],
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse { .. } => f.write_str("failed to parse MLIR")?,
+            Error::Verify { .. } => f.write_str("MLIR verification failed")?,
+            Error::NullHandle { kind, .. } => write!(f, "MLIR returned a null {kind}")?,
+            Error::Bytecode { .. } => f.write_str("failed to write MLIR bytecode")?,
+            Error::Io(e) => write!(f, "I/O error: {e}")?,
+            Error::InvalidUtf8(e) => write!(f, "invalid UTF-8 in MLIR string: {e}")?,
+            Error::TypeMismatch { expected, found } => {
+                write!(f, "expected {expected}, found {found}")?
+            }
+            Error::OutOfRange { value, ty } => write!(f, "{value} is out of range for {ty}")?,
+            Error::InvalidArgument(message) => write!(f, "invalid argument: {message}")?,
+        }
+
+        for diag in self.diagnostics() {
+            write!(f, "\n{diag}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::InvalidUtf8(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(value: Utf8Error) -> Self {
+        Error::InvalidUtf8(value)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Error::Io(value)
+    }
+}
@@ -1,69 +1,45 @@
 //! Rust bindings to the MLIR project.
 
 use std::{
+    cmp::Ordering,
     ffi::{c_char, c_uint, c_void},
     fmt::{self, Formatter},
+    fs,
+    hash::{Hash, Hasher},
+    io,
     marker::PhantomData,
     mem::ManuallyDrop,
-    ops::{Deref, DerefMut},
+    ops::Deref,
+    path::Path,
     ptr, slice,
-    sync::Mutex,
 };
 
-use mlir_sys as ffi;
-use once_cell::sync::OnceCell;
+#[doc(hidden)]
+pub use mlir_sys as ffi;
 use ty::TypeSubtype;
 
-use crate::attr::TypeAttr;
+use crate::attr::{AttributeSubtype, TypeAttr};
 
 pub mod attr;
+pub mod bytecode;
+pub mod convert;
 pub mod cursor;
+pub mod diag;
+mod error;
+pub mod print;
 pub mod ty;
 
-#[doc(hidden)]
-pub struct SyncContext {
-    context: Context,
-    mutex: Mutex<()>,
-}
-
-unsafe impl Send for SyncContext {}
-unsafe impl Sync for SyncContext {}
-
-impl SyncContext {
-    /// Calls the provided closure without locking the underlying context.
-    ///
-    /// This is safe only for operations which the context synchronizes internally, particularly
-    /// certain operations on uniqued values.
-    pub fn without_mutex<F, T>(&self, f: F) -> T
-    where
-        F: FnOnce(ffi::MlirContext) -> T,
-    {
-        f(self.context.inner)
-    }
-
-    /// Calls the provided closure with the underlying context under a mutex.
-    ///
-    /// This is required for operations which the context does not synchronize internally.
-    pub fn with_mutex<F, T>(&self, f: F) -> T
-    where
-        F: FnOnce(ffi::MlirContext) -> T,
-    {
-        let _guard = self.mutex.lock().unwrap();
-        f(self.context.inner)
-    }
-}
-
-static CONTEXT: OnceCell<SyncContext> = OnceCell::new();
+pub use error::Error;
 
 // Defines wrappers around semantically owned MLIR values.
 macro_rules! owned_types {
     ($(
         $(#[$attr:meta])*
-        $v:vis struct $name:ident = $inner:path;
+        $v:vis struct $name:ident $(<$lt:lifetime>)? = $inner:path;
     )*) => {
         $(
-            struct_def!($(#[$attr])* $v $name, $inner);
-            raw_impls!($name, $inner);
+            struct_def!($(#[$attr])* $v $name $(<$lt>)?, $inner);
+            raw_impls!($name $(<$lt>)?, $inner);
         )*
     };
 }
@@ -72,40 +48,152 @@ macro_rules! owned_types {
 macro_rules! semi_owned_types {
     ($(
         $(#[$attr:meta])*
-        $v:vis struct $name:ident = $inner:path;
+        $v:vis struct $name:ident<$lt:lifetime> = $inner:path;
     )*) => {
         $(
-            struct_def!($(#[$attr])* $v $name, $inner);
-            raw_impls!($name, $inner);
+            struct_def!($(#[$attr])* $v $name<$lt>, $inner);
+            raw_impls!($name<$lt>, $inner);
         )*
     };
 }
 
-// Defines wrappers around semantically borrowed MLIR values.
+// Defines references to owned MLIR values which are linked into a parent.
+//
+// References dereference to the owned type, which provides the read-only API. Mutable references
+// deliberately do not implement `DerefMut`, as a `&mut Block` referring to a linked block could be
+// swapped with an owned `Block`. Instead, mutating methods are defined on the mutable reference
+// types themselves.
 macro_rules! borrowed_types {
-    ($(
+    () => {};
+
+    (
         $(#[$attr:meta])*
-        $v:vis struct $name:ident = $inner:path;
-    )*) => {
-        $(
-            struct_def!($(#[$attr])* #[derive(Copy, Clone)] $v $name<'a>, $inner);
-            raw_impls!($name<'a>, $inner);
-        )*
+        $v:vis struct $name:ident: &mut $owned:ident = $inner:path;
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        #[repr(transparent)]
+        $v struct $name<'cx, 'a> {
+            pub(crate) inner: $inner,
+            pub(crate) phantom: PhantomData<&'a mut $owned<'cx>>,
+        }
+
+        impl private::Sealed for $name<'_, '_> {}
+
+        raw_impls!($name<'cx, 'a>, $inner);
+
+        impl<'cx> $name<'cx, '_> {
+            #[doc = concat!("Reborrows this `", stringify!($name), "` for a shorter lifetime.")]
+            #[inline]
+            pub fn reborrow(&mut self) -> $name<'cx, '_> {
+                $name {
+                    inner: self.inner,
+                    phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<'cx> $owned<'cx> {
+            #[doc = concat!("Borrows this `", stringify!($owned), "` as a [`", stringify!($name), "`].")]
+            #[inline]
+            pub fn by_mut(&mut self) -> $name<'cx, '_> {
+                $name {
+                    inner: self.inner,
+                    phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<'cx> Deref for $name<'cx, '_> {
+            type Target = $owned<'cx>;
+
+            fn deref(&self) -> &Self::Target {
+                unsafe { &*(self as *const $name as *const $owned) }
+            }
+        }
+
+        borrowed_types!($($rest)*);
+    };
+
+    (
+        $(#[$attr:meta])*
+        $v:vis struct $name:ident: &$owned:ident = $inner:path;
+        $($rest:tt)*
+    ) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone)]
+        #[repr(transparent)]
+        $v struct $name<'cx, 'a> {
+            pub(crate) inner: $inner,
+            pub(crate) phantom: PhantomData<&'a $owned<'cx>>,
+        }
+
+        impl private::Sealed for $name<'_, '_> {}
+
+        raw_impls!($name<'cx, 'a>, $inner);
+
+        impl<'cx> $owned<'cx> {
+            #[doc = concat!("Borrows this `", stringify!($owned), "` as a [`", stringify!($name), "`].")]
+            #[inline]
+            pub fn by_ref(&self) -> $name<'cx, '_> {
+                $name {
+                    inner: self.inner,
+                    phantom: PhantomData,
+                }
+            }
+        }
+
+        impl<'cx> Deref for $name<'cx, '_> {
+            type Target = $owned<'cx>;
+
+            fn deref(&self) -> &Self::Target {
+                unsafe { &*(self as *const $name as *const $owned) }
+            }
+        }
+
+        borrowed_types!($($rest)*);
     };
 }
 
+// Uniqued types are hashed and ordered by pointer. The order is stable for the lifetime of the
+// context, but not across runs.
 macro_rules! uniqued_types {
     ($(
         $(#[$attr:meta])*
-        $v:vis struct $name:ident = $inner:path;
+        $v:vis struct $name:ident<$lt:lifetime> = $inner:path;
     )*) => {
         $(
-            struct_def!($(#[$attr])* #[derive(Copy, Clone)] $v $name, $inner);
-            raw_impls!($name, $inner);
+            struct_def!($(#[$attr])* #[derive(Copy, Clone)] $v $name<$lt>, $inner);
+            raw_impls!($name<$lt>, $inner);
+            impl_ptr_ord!($name<$lt>);
         )*
     };
 }
 
+// Implements `Hash` and `Ord` by pointer.
+macro_rules! impl_ptr_ord {
+    ($name:ident<$($lt:lifetime),+>) => {
+        impl<$($lt),+> Hash for $name<$($lt),+> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.inner.ptr.hash(state);
+            }
+        }
+
+        impl<$($lt),+> PartialOrd for $name<$($lt),+> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl<$($lt),+> Ord for $name<$($lt),+> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.inner.ptr.cmp(&other.inner.ptr)
+            }
+        }
+    };
+}
+
+// Types which depend on a `Context` carry its lifetime as `'cx`.
 macro_rules! struct_def {
     ($(#[$m:meta])* $v:vis $name:ident $(<$lt:lifetime>)? , $inner:path) => {
         $(#[$m])*
@@ -113,14 +201,14 @@ macro_rules! struct_def {
         $v struct $name $(<$lt>)? {
             #[allow(dead_code)]
             pub(crate) inner: $inner,
-            $(pub (crate) phantom: PhantomData<&$lt ()>,)?
+            $(pub (crate) phantom: PhantomData<&$lt Context>,)?
         }
 
         impl$(<$lt>)? private::Sealed for $name$(<$lt>)? {}
     };
 }
 
-// This macro requires two definitions because the optional lifetime doesn't appear in the
+// This macro requires two definitions because the optional lifetimes don't appear in the
 // `phantom: PhantomData` field assignment.
 macro_rules! raw_impls {
     ($name:ident, $inner:path) => {
@@ -149,8 +237,8 @@ macro_rules! raw_impls {
         }
     };
 
-    ($name:ident <$lt:lifetime> , $inner:path) => {
-        impl<$lt> $name<$lt> {
+    ($name:ident <$($lt:lifetime),+> , $inner:path) => {
+        impl<$($lt),+> $name<$($lt),+> {
             #[doc = concat!("Construct a `", stringify!($name), "` from its C API equivalent.")]
             ///
             /// # Safety
@@ -158,7 +246,7 @@ macro_rules! raw_impls {
             /// Calling this constructor must not result in duplicate ownership or mutable aliasing.
             #[allow(dead_code)]
             #[inline]
-            pub unsafe fn from_raw(raw: $inner) -> Option<$name<$lt>> {
+            pub unsafe fn from_raw(raw: $inner) -> Option<$name<$($lt),+>> {
                 if raw.ptr.is_null() {
                     return None;
                 }
@@ -184,17 +272,40 @@ macro_rules! impl_display {
         $(
             impl$(<$lt>)? fmt::Display for $name$(<$lt2>)? {
                 fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-                    let mut userdata = FmtUserdata::new(f);
+                    unsafe { fmt_print(f, self.inner, $print_fn) }
+                }
+            }
+
+            impl$(<$lt>)? $name$(<$lt2>)? {
+                #[doc = concat!("Writes the textual form of this `", stringify!($name), "` to `w`.")]
+                ///
+                /// The output is streamed to `w` as it is printed, and need not be valid UTF-8. Unlike
+                /// the `Display` impl, which writes invalid UTF-8 as U+FFFD, the bytes are passed
+                /// through unchanged.
+                pub fn write_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+                    let mut userdata = IoUserdata::new(w);
 
                     unsafe {
                         $print_fn(
                             self.inner,
-                            Some(fmt_callback::<Formatter<'_>>),
-                            &mut userdata as *mut FmtUserdata<Formatter<'_>> as *mut c_void,
+                            Some(io_callback::<W>),
+                            &mut userdata as *mut IoUserdata<W> as *mut c_void,
                         );
                     }
 
-                    Ok(())
+                    userdata.error.map_or(Ok(()), Err)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_debug {
+    ($(impl$(<$($lt:lifetime),+>)? fmt::Debug for $name:ident$(<$($lt2:lifetime),+>)? = $print_fn:path;)*) => {
+        $(
+            impl$(<$($lt),+>)? fmt::Debug for $name$(<$($lt2),+>)? {
+                fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                    unsafe { fmt_print(f, self.inner, $print_fn) }
                 }
             }
         )*
@@ -202,30 +313,141 @@ macro_rules! impl_display {
 }
 
 macro_rules! impl_eq {
-    ($(impl$(<$lt:lifetime>)? Eq for $name:ident$(<$lt2:lifetime>)? = $eq_fn:path;)*) => {
+    ($(impl$(<$($lt:lifetime),+>)? Eq for $name:ident$(<$($lt2:lifetime),+>)? = $eq_fn:path;)*) => {
         $(
-            impl$(<$lt>)? PartialEq for $name$(<$lt2>)? {
+            impl$(<$($lt),+>)? PartialEq for $name$(<$($lt2),+>)? {
                 #[inline]
                 fn eq(&self, other: &Self) -> bool {
                     unsafe { $eq_fn(self.inner, other.inner) }
                 }
             }
 
-            impl$(<$lt>)? Eq for $name$(<$lt2>)? {}
+            impl$(<$($lt),+>)? Eq for $name$(<$($lt2),+>)? {}
         )*
     };
 }
 
+/// Implements `Eq`, `Ord`, `Hash`, `Display` and `Debug` for a subtype wrapper by delegating to
+/// the handle it converts into, e.g. `impl_subtype_traits!(StringAttr => Attribute<'_>)`.
+///
+/// This is exported for use by [`attr_types!`]; the wrapper must be `Copy`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_subtype_traits {
+    ($name:ident => $base:ty) => {
+        impl PartialEq for $name<'_> {
+            fn eq(&self, other: &Self) -> bool {
+                <$base>::from(*self) == <$base>::from(*other)
+            }
+        }
+
+        impl Eq for $name<'_> {}
+
+        impl ::std::hash::Hash for $name<'_> {
+            fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+                ::std::hash::Hash::hash(&<$base>::from(*self), state);
+            }
+        }
+
+        impl PartialOrd for $name<'_> {
+            fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $name<'_> {
+            fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+                <$base>::from(*self).cmp(&<$base>::from(*other))
+            }
+        }
+
+        impl ::std::fmt::Display for $name<'_> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                ::std::fmt::Display::fmt(&<$base>::from(*self), f)
+            }
+        }
+
+        impl ::std::fmt::Debug for $name<'_> {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                ::std::fmt::Debug::fmt(&<$base>::from(*self), f)
+            }
+        }
+    };
+}
+
 // User data object for use in MLIR formatting callbacks.
+//
+// MLIR output need not be valid UTF-8, so invalid sequences are written as U+FFFD. A character may
+// be split across callbacks, so an incomplete sequence at the end of one is held back until the
+// next.
 struct FmtUserdata<'fmt, W: fmt::Write> {
     w: &'fmt mut W,
+    pending: Vec<u8>,
     error: Option<fmt::Error>,
 }
 
 impl<'fmt, W: fmt::Write> FmtUserdata<'fmt, W> {
     fn new(w: &'fmt mut W) -> FmtUserdata<'fmt, W> {
-        FmtUserdata { w, error: None }
+        FmtUserdata {
+            w,
+            pending: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn write_lossy(&mut self, mut bytes: &[u8]) -> fmt::Result {
+        loop {
+            match std::str::from_utf8(bytes) {
+                Ok(s) => return self.w.write_str(s),
+                Err(e) => {
+                    let (valid, rest) = bytes.split_at(e.valid_up_to());
+                    // `from_utf8` validated everything before `valid_up_to`.
+                    self.w
+                        .write_str(unsafe { std::str::from_utf8_unchecked(valid) })?;
+
+                    let Some(len) = e.error_len() else {
+                        self.pending.extend_from_slice(rest);
+                        return Ok(());
+                    };
+                    self.w.write_char(char::REPLACEMENT_CHARACTER)?;
+                    bytes = &rest[len..];
+                }
+            }
+        }
+    }
+
+    // Returns the first error encountered, after writing out any incomplete trailing sequence.
+    fn finish(self) -> fmt::Result {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+
+        if !self.pending.is_empty() {
+            self.w.write_char(char::REPLACEMENT_CHARACTER)?;
+        }
+        Ok(())
+    }
+}
+
+// Writes the output of an MLIR print function, e.g. `mlirTypePrint`, to `f`.
+//
+// Safety: `inner` must be a valid handle for `print_fn`.
+unsafe fn fmt_print<T>(
+    f: &mut Formatter<'_>,
+    inner: T,
+    print_fn: unsafe extern "C" fn(T, ffi::MlirStringCallback, *mut c_void),
+) -> fmt::Result {
+    let mut userdata = FmtUserdata::new(f);
+
+    unsafe {
+        print_fn(
+            inner,
+            Some(fmt_callback::<Formatter<'_>>),
+            &mut userdata as *mut FmtUserdata<Formatter<'_>> as *mut c_void,
+        );
     }
+
+    userdata.finish()
 }
 
 /// MLIR string callback for writing to a `fmt::Formatter`.
@@ -241,95 +463,191 @@ pub unsafe extern "C" fn fmt_callback<W: fmt::Write>(s: ffi::MlirStringRef, user
     }
 
     let bytes = unsafe { slice::from_raw_parts(s.data as *const u8, s.length) };
-    let Ok(utf8) = std::str::from_utf8(bytes) else {
-        userdata.error = Some(fmt::Error);
-        return
+    let result = if userdata.pending.is_empty() {
+        userdata.write_lossy(bytes)
+    } else {
+        let mut pending = std::mem::take(&mut userdata.pending);
+        pending.extend_from_slice(bytes);
+        userdata.write_lossy(&pending)
     };
 
-    let _ = write!(userdata.w, "{utf8}");
+    if let Err(e) = result {
+        userdata.error = Some(e);
+    }
+}
+
+// User data object for use in MLIR callbacks which write to an `io::Write`.
+struct IoUserdata<'w, W: io::Write> {
+    w: &'w mut W,
+    error: Option<io::Error>,
 }
 
-#[doc(hidden)]
-pub fn context() -> &'static SyncContext {
-    CONTEXT.get_or_init(|| SyncContext {
-        context: Context::create().expect("Context creation failed."),
-        mutex: Mutex::new(()),
-    })
+impl<'w, W: io::Write> IoUserdata<'w, W> {
+    fn new(w: &'w mut W) -> IoUserdata<'w, W> {
+        IoUserdata { w, error: None }
+    }
+}
+
+/// MLIR string callback for writing to an `io::Write`.
+///
+/// # Safety
+///
+/// - `userdata` must be safely upgradable to `IoUserdata`.
+unsafe extern "C" fn io_callback<W: io::Write>(s: ffi::MlirStringRef, userdata: *mut c_void) {
+    let Some(userdata) = (unsafe { userdata.cast::<IoUserdata<'_, W>>().as_mut() }) else {
+        return;
+    };
+    if userdata.error.is_some() {
+        return;
+    }
+
+    let bytes = unsafe { slice::from_raw_parts(s.data as *const u8, s.length) };
+    if let Err(e) = userdata.w.write_all(bytes) {
+        userdata.error = Some(e);
+    }
 }
 
 owned_types! {
     /// A list of operations (a basic block).
-    pub struct Block = ffi::MlirBlock;
+    pub struct Block<'cx> = ffi::MlirBlock;
     /// A top-level context object for a collection of MLIR operations.
-    struct Context = ffi::MlirContext;
+    pub struct Context = ffi::MlirContext;
     /// A handle used to register a dialect with a [`Context`].
     pub struct DialectHandle = ffi::MlirDialectHandle;
     /// A registry of dialects available to a [`Context`].
     pub struct DialectRegistry = ffi::MlirDialectRegistry;
     /// An MLIR operation.
-    pub struct Operation = ffi::MlirOperation;
+    pub struct Operation<'cx> = ffi::MlirOperation;
     /// A list of basic blocks attached to a parent operation.
-    pub struct Region = ffi::MlirRegion;
+    pub struct Region<'cx> = ffi::MlirRegion;
 }
 
 semi_owned_types! {
     /// A collection of operations, attributes and types associated with a unique namespace.
-    pub struct Dialect = ffi::MlirDialect;
+    pub struct Dialect<'cx> = ffi::MlirDialect;
     /// A top-level unit of MLIR.
-    pub struct Module = ffi::MlirModule;
+    pub struct Module<'cx> = ffi::MlirModule;
     /// A set of unique symbols associated with an operation.
-    pub struct SymbolTable = ffi::MlirSymbolTable;
+    pub struct SymbolTable<'cx> = ffi::MlirSymbolTable;
 }
 
 borrowed_types! {
     /// A reference to a [`Block`].
-    pub struct BlockRef = ffi::MlirBlock;
+    pub struct BlockRef: &Block = ffi::MlirBlock;
     /// A mutable reference to a [`Block`].
-    pub struct BlockMut = ffi::MlirBlock;
+    pub struct BlockMut: &mut Block = ffi::MlirBlock;
     /// A reference to an [`Operation`].
-    pub struct OperationRef = ffi::MlirOperation;
+    pub struct OperationRef: &Operation = ffi::MlirOperation;
     /// A mutable reference to an [`Operation`].
-    pub struct OperationMut = ffi::MlirOperation;
+    pub struct OperationMut: &mut Operation = ffi::MlirOperation;
     /// A reference to a [`Region`].
-    pub struct RegionRef = ffi::MlirRegion;
+    pub struct RegionRef: &Region = ffi::MlirRegion;
     /// A mutable reference to a [`Region`].
-    pub struct RegionMut = ffi::MlirRegion;
+    pub struct RegionMut: &mut Region = ffi::MlirRegion;
 }
 
 uniqued_types! {
+    /// A mapping from dimension and symbol identifiers to a list of affine expressions.
+    pub struct AffineMap<'cx> = ffi::MlirAffineMap;
     /// A (compile-time) constant value associated with an operation.
-    pub struct Attribute = ffi::MlirAttribute;
-    pub struct Identifier = ffi::MlirIdentifier;
-    pub struct Location = ffi::MlirLocation;
-    pub struct Type = ffi::MlirType;
-    pub struct Value = ffi::MlirValue;
+    pub struct Attribute<'cx> = ffi::MlirAttribute;
+    pub struct Identifier<'cx> = ffi::MlirIdentifier;
+    /// A set of affine constraints on dimension and symbol identifiers.
+    pub struct IntegerSet<'cx> = ffi::MlirIntegerSet;
+    pub struct Location<'cx> = ffi::MlirLocation;
+    pub struct Type<'cx> = ffi::MlirType;
+}
+
+/// A value defined by an operation or block, which is used as an operand by other operations.
+///
+/// Values borrow the IR they were obtained from as `'a`, so they cannot outlive it. To modify a
+/// value, borrow it as a [`ValueMut`] from the operation or block which defines it.
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct Value<'cx, 'a> {
+    pub(crate) inner: ffi::MlirValue,
+    pub(crate) phantom: PhantomData<&'a Operation<'cx>>,
+}
+
+impl private::Sealed for Value<'_, '_> {}
+
+raw_impls!(Value<'cx, 'a>, ffi::MlirValue);
+impl_ptr_ord!(Value<'cx, 'a>);
+
+/// A mutable reference to a [`Value`], obtained from [`OperationMut::result_mut`] or
+/// [`BlockMut::argument_mut`].
+#[repr(transparent)]
+pub struct ValueMut<'cx, 'a> {
+    pub(crate) inner: ffi::MlirValue,
+    pub(crate) phantom: PhantomData<&'a mut Operation<'cx>>,
+}
+
+raw_impls!(ValueMut<'cx, 'a>, ffi::MlirValue);
+
+/// A use of a [`Value`] as an operand of an operation.
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct OpOperand<'cx, 'a> {
+    pub(crate) inner: ffi::MlirOpOperand,
+    pub(crate) phantom: PhantomData<&'a Operation<'cx>>,
+}
+
+impl private::Sealed for OpOperand<'_, '_> {}
+
+raw_impls!(OpOperand<'cx, 'a>, ffi::MlirOpOperand);
+impl_ptr_ord!(OpOperand<'cx, 'a>);
+
+/// A reference to a [`Context`], obtained from an object which depends on it.
+#[derive(Copy, Clone)]
+#[repr(transparent)]
+pub struct ContextRef<'cx> {
+    pub(crate) inner: ffi::MlirContext,
+    pub(crate) phantom: PhantomData<&'cx Context>,
 }
 
+impl private::Sealed for ContextRef<'_> {}
+
+raw_impls!(ContextRef<'cx>, ffi::MlirContext);
+
 impl_eq! {
-    impl Eq for Attribute = ffi::mlirAttributeEqual;
-    impl Eq for Block = ffi::mlirBlockEqual;
+    impl<'cx> Eq for AffineMap<'cx> = ffi::mlirAffineMapEqual;
+    impl<'cx> Eq for Attribute<'cx> = ffi::mlirAttributeEqual;
+    impl<'cx> Eq for Block<'cx> = ffi::mlirBlockEqual;
     impl Eq for Context = ffi::mlirContextEqual;
-    impl Eq for Dialect = ffi::mlirDialectEqual;
-    impl Eq for Location = ffi::mlirLocationEqual;
-    impl Eq for Operation = ffi::mlirOperationEqual;
-    impl Eq for Region = ffi::mlirRegionEqual;
-    impl Eq for Type = ffi::mlirTypeEqual;
-    impl Eq for Value = ffi::mlirValueEqual;
+    impl<'cx> Eq for Dialect<'cx> = ffi::mlirDialectEqual;
+    impl<'cx> Eq for Identifier<'cx> = ffi::mlirIdentifierEqual;
+    impl<'cx> Eq for IntegerSet<'cx> = ffi::mlirIntegerSetEqual;
+    impl<'cx> Eq for Location<'cx> = ffi::mlirLocationEqual;
+    impl<'cx> Eq for Operation<'cx> = ffi::mlirOperationEqual;
+    impl<'cx> Eq for Region<'cx> = ffi::mlirRegionEqual;
+    impl<'cx> Eq for Type<'cx> = ffi::mlirTypeEqual;
+    impl<'cx, 'a> Eq for Value<'cx, 'a> = ffi::mlirValueEqual;
 }
 
 impl_display! {
-    impl fmt::Display for Attribute = ffi::mlirAttributePrint;
-    impl fmt::Display for Location = ffi::mlirLocationPrint;
-    impl fmt::Display for Operation = ffi::mlirOperationPrint;
-    impl fmt::Display for Type = ffi::mlirTypePrint;
+    impl<'cx> fmt::Display for AffineMap<'cx> = ffi::mlirAffineMapPrint;
+    impl<'cx> fmt::Display for Attribute<'cx> = ffi::mlirAttributePrint;
+    impl<'cx> fmt::Display for IntegerSet<'cx> = ffi::mlirIntegerSetPrint;
+    impl<'cx> fmt::Display for Location<'cx> = ffi::mlirLocationPrint;
+    impl<'cx> fmt::Display for Operation<'cx> = ffi::mlirOperationPrint;
+    impl<'cx> fmt::Display for Type<'cx> = ffi::mlirTypePrint;
+}
+
+impl_debug! {
+    impl<'cx> fmt::Debug for AffineMap<'cx> = ffi::mlirAffineMapPrint;
+    impl<'cx> fmt::Debug for Attribute<'cx> = ffi::mlirAttributePrint;
+    impl<'cx> fmt::Debug for IntegerSet<'cx> = ffi::mlirIntegerSetPrint;
+    impl<'cx> fmt::Debug for Location<'cx> = ffi::mlirLocationPrint;
+    impl<'cx> fmt::Debug for Type<'cx> = ffi::mlirTypePrint;
+    impl<'cx, 'a> fmt::Debug for Value<'cx, 'a> = ffi::mlirValuePrint;
 }
 
 /// Defines methods of the form `fn(&self) -> bool`.
 macro_rules! is_fns {
-    (impl$(<$lt:lifetime>)? $name:ident$(<$lt2:lifetime>)? {
+    (impl$(<$($lt:lifetime),+>)? $name:ident$(<$($lt2:lifetime),+>)? {
         $($v:vis fn $fn_name:ident = $ffi_name:path;)*
     }) => {
-        impl $(<$lt>)? $name $(<$lt2>)? {
+        impl $(<$($lt),+>)? $name $(<$($lt2),+>)? {
             #[inline]
             $($v fn $fn_name(&self) -> bool {
                 unsafe { $ffi_name(self.inner) }
@@ -338,13 +656,205 @@ macro_rules! is_fns {
     };
 }
 
+/// Defines iterators over MLIR containers whose elements are accessed by index.
+macro_rules! indexed_iters {
+    ($(
+        $(#[$attr:meta])*
+        $v:vis struct $name:ident<$cx:lifetime, $lt:lifetime>: $parent:ident => $item:ty {
+            len: $len_fn:path,
+            get: $get_fn:path,
+        }
+    )*) => {
+        $(
+            $(#[$attr])*
+            $v struct $name<$cx, $lt> {
+                parent: ffi::$parent,
+                front: usize,
+                back: usize,
+                phantom: PhantomData<&$lt ContextRef<$cx>>,
+            }
+
+            impl<$cx, $lt> $name<$cx, $lt> {
+                /// # Safety
+                ///
+                /// `parent` must be valid for the lifetime of the iterator.
+                #[allow(dead_code)]
+                pub(crate) unsafe fn new(parent: ffi::$parent) -> $name<$cx, $lt> {
+                    $name {
+                        parent,
+                        front: 0,
+                        back: unsafe { $len_fn(parent) } as usize,
+                        phantom: PhantomData,
+                    }
+                }
+            }
+
+            impl<$cx, $lt> Iterator for $name<$cx, $lt> {
+                type Item = $item;
+
+                fn next(&mut self) -> Option<Self::Item> {
+                    if self.front == self.back {
+                        return None;
+                    }
+
+                    let item = unsafe { <$item>::from_raw($get_fn(self.parent, self.front as isize)) };
+                    self.front += 1;
+                    item
+                }
+
+                fn size_hint(&self) -> (usize, Option<usize>) {
+                    let len = self.back - self.front;
+                    (len, Some(len))
+                }
+            }
+
+            impl<$cx, $lt> DoubleEndedIterator for $name<$cx, $lt> {
+                fn next_back(&mut self) -> Option<Self::Item> {
+                    if self.front == self.back {
+                        return None;
+                    }
+
+                    self.back -= 1;
+                    unsafe { <$item>::from_raw($get_fn(self.parent, self.back as isize)) }
+                }
+            }
+
+            impl<$cx, $lt> ExactSizeIterator for $name<$cx, $lt> {}
+
+            impl<$cx, $lt> std::iter::FusedIterator for $name<$cx, $lt> {}
+        )*
+    };
+}
+
+// AffineMap ==================================================================
+
+impl<'cx> AffineMap<'cx> {
+    /// Returns the affine map with no dimensions, symbols or results.
+    pub fn empty(cx: &'cx Context) -> AffineMap<'cx> {
+        AffineMap::try_empty(cx).expect("unexpected MLIR error: AffineMap should be non-null")
+    }
+
+    pub fn try_empty(cx: &'cx Context) -> Result<AffineMap<'cx>, Error> {
+        unsafe { AffineMap::from_raw(ffi::mlirAffineMapEmptyGet(cx.inner)) }
+            .ok_or_else(|| Error::null_handle("AffineMap"))
+    }
+
+    /// Returns the affine map with the given dimensions and symbols, and no results.
+    pub fn zero_result(cx: &'cx Context, num_dims: usize, num_symbols: usize) -> AffineMap<'cx> {
+        AffineMap::try_zero_result(cx, num_dims, num_symbols)
+            .expect("unexpected MLIR error: AffineMap should be non-null")
+    }
+
+    pub fn try_zero_result(
+        cx: &'cx Context,
+        num_dims: usize,
+        num_symbols: usize,
+    ) -> Result<AffineMap<'cx>, Error> {
+        unsafe {
+            AffineMap::from_raw(ffi::mlirAffineMapZeroResultGet(
+                cx.inner,
+                num_dims as isize,
+                num_symbols as isize,
+            ))
+        }
+        .ok_or_else(|| Error::null_handle("AffineMap"))
+    }
+
+    /// Returns the affine map with a single constant result, e.g. `() -> (42)`.
+    pub fn constant(cx: &'cx Context, value: i64) -> AffineMap<'cx> {
+        AffineMap::try_constant(cx, value)
+            .expect("unexpected MLIR error: AffineMap should be non-null")
+    }
+
+    pub fn try_constant(cx: &'cx Context, value: i64) -> Result<AffineMap<'cx>, Error> {
+        unsafe { AffineMap::from_raw(ffi::mlirAffineMapConstantGet(cx.inner, value)) }
+            .ok_or_else(|| Error::null_handle("AffineMap"))
+    }
+
+    /// Returns the identity affine map on `num_dims` dimensions, e.g. `(d0, d1) -> (d0, d1)`.
+    pub fn identity(cx: &'cx Context, num_dims: usize) -> AffineMap<'cx> {
+        AffineMap::try_identity(cx, num_dims)
+            .expect("unexpected MLIR error: AffineMap should be non-null")
+    }
+
+    pub fn try_identity(cx: &'cx Context, num_dims: usize) -> Result<AffineMap<'cx>, Error> {
+        unsafe {
+            AffineMap::from_raw(ffi::mlirAffineMapMultiDimIdentityGet(
+                cx.inner,
+                num_dims as isize,
+            ))
+        }
+        .ok_or_else(|| Error::null_handle("AffineMap"))
+    }
+
+    /// Returns the affine map which permutes its dimensions, e.g. `(d0, d1) -> (d1, d0)` for
+    /// the permutation `[1, 0]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `permutation` is not a permutation of `0..permutation.len()`.
+    pub fn permutation(cx: &'cx Context, permutation: &[u32]) -> AffineMap<'cx> {
+        AffineMap::try_permutation(cx, permutation).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns the affine map which permutes its dimensions.
+    ///
+    /// Returns [`Error::InvalidArgument`] if `permutation` is not a permutation of
+    /// `0..permutation.len()`.
+    pub fn try_permutation(cx: &'cx Context, permutation: &[u32]) -> Result<AffineMap<'cx>, Error> {
+        let mut sorted = permutation.to_vec();
+        sorted.sort_unstable();
+        if !sorted.iter().enumerate().all(|(i, &p)| i == p as usize) {
+            return Err(Error::InvalidArgument(format!(
+                "{permutation:?} is not a permutation"
+            )));
+        }
+
+        let mut permutation = permutation.iter().map(|&p| p as c_uint).collect::<Vec<_>>();
+
+        unsafe {
+            AffineMap::from_raw(ffi::mlirAffineMapPermutationGet(
+                cx.inner,
+                permutation.len() as isize,
+                permutation.as_mut_ptr(),
+            ))
+        }
+        .ok_or_else(|| Error::null_handle("AffineMap"))
+    }
+
+    pub fn num_dims(&self) -> usize {
+        unsafe { ffi::mlirAffineMapGetNumDims(self.inner) as usize }
+    }
+
+    pub fn num_symbols(&self) -> usize {
+        unsafe { ffi::mlirAffineMapGetNumSymbols(self.inner) as usize }
+    }
+
+    pub fn num_results(&self) -> usize {
+        unsafe { ffi::mlirAffineMapGetNumResults(self.inner) as usize }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        unsafe { ffi::mlirAffineMapIsIdentity(self.inner) }
+    }
+
+    pub fn context(&self) -> ContextRef<'cx> {
+        unsafe {
+            ContextRef::from_raw(ffi::mlirAffineMapGetContext(self.inner))
+                .expect("unexpected MLIR error: AffineMap context should be non-null")
+        }
+    }
+}
+
 // Attribute ==================================================================
 
 is_fns! {
-    impl Attribute {
+    impl<'cx> Attribute<'cx> {
         pub fn is_affine_map = ffi::mlirAttributeIsAAffineMap;
         pub fn is_array = ffi::mlirAttributeIsAArray;
         pub fn is_bool = ffi::mlirAttributeIsABool;
+        pub fn is_dense_elements = ffi::mlirAttributeIsADenseElements;
+        pub fn is_dense_resource_elements = ffi::mlirAttributeIsADenseResourceElements;
         pub fn is_dictionary = ffi::mlirAttributeIsADictionary;
         pub fn is_flat_symbol_ref = ffi::mlirAttributeIsAFlatSymbolRef;
         pub fn is_float = ffi::mlirAttributeIsAFloat;
@@ -353,79 +863,162 @@ is_fns! {
         pub fn is_opaque = ffi::mlirAttributeIsAOpaque;
         pub fn is_string = ffi::mlirAttributeIsAString;
         pub fn is_symbol_ref = ffi::mlirAttributeIsASymbolRef;
+        pub fn is_type = ffi::mlirAttributeIsAType;
         pub fn is_unit = ffi::mlirAttributeIsAUnit;
     }
 }
 pub(crate) use is_fns;
 
-impl Attribute {
+impl<'cx> Attribute<'cx> {
+    #[inline]
+    pub fn array(cx: &'cx Context, elements: &[Attribute<'cx>]) -> Attribute<'cx> {
+        Attribute::try_array(cx, elements)
+            .expect("unexpected MLIR error: ArrayAttr should be non-null")
+    }
+
     #[inline]
-    pub fn array(elements: &[Attribute]) -> Attribute {
-        // Safety: attribute creation is synchronized internally.
-        context().without_mutex(|cx| unsafe {
+    pub fn try_array(
+        cx: &'cx Context,
+        elements: &[Attribute<'cx>],
+    ) -> Result<Attribute<'cx>, Error> {
+        unsafe {
             Attribute::from_raw(ffi::mlirArrayAttrGet(
-                cx,
+                cx.inner,
                 elements.len() as isize,
                 elements.as_ptr() as *const _,
             ))
-            .unwrap()
-        })
+        }
+        .ok_or_else(|| Error::null_handle("ArrayAttr"))
     }
 
     #[inline]
-    pub fn string<'a, S: Into<StringRef<'a>>>(s: S) -> Attribute {
-        // Safety: attribute creation is synchronized internally.
-        context().without_mutex(|cx| unsafe {
-            Attribute::from_raw(ffi::mlirStringAttrGet(cx, s.into().inner)).unwrap()
-        })
+    pub fn string<'a, S: Into<StringRef<'a>>>(cx: &'cx Context, s: S) -> Attribute<'cx> {
+        Attribute::try_string(cx, s).expect("unexpected MLIR error: StringAttr should be non-null")
     }
-}
 
-impl<'a, S> From<S> for Attribute
-where
-    S: Into<StringRef<'a>>,
-{
     #[inline]
-    fn from(value: S) -> Self {
-        Attribute::string(value)
+    pub fn try_string<'a, S: Into<StringRef<'a>>>(
+        cx: &'cx Context,
+        s: S,
+    ) -> Result<Attribute<'cx>, Error> {
+        unsafe { Attribute::from_raw(ffi::mlirStringAttrGet(cx.inner, s.into().inner)) }
+            .ok_or_else(|| Error::null_handle("StringAttr"))
     }
-}
 
-impl From<Identifier> for Attribute {
+    /// Parses an attribute from its textual form, e.g. `dense<1> : tensor<2xi32>`.
+    ///
+    /// If parsing fails, the returned error holds the diagnostics emitted by the parser.
+    pub fn parse<'a, S: Into<StringRef<'a>>>(
+        cx: &'cx Context,
+        s: S,
+    ) -> Result<Attribute<'cx>, Error> {
+        let s = s.into();
+        let (attr, diagnostics) = cx.with_diagnostics(|| unsafe {
+            Attribute::from_raw(ffi::mlirAttributeParseGet(cx.inner, s.inner))
+        });
+
+        attr.ok_or(Error::Parse { diagnostics })
+    }
+
+    pub fn downcast<T: AttributeSubtype<'cx>>(self) -> Result<T, Self> {
+        T::downcast_from(self)
+    }
+
+    /// Returns the type of this attribute.
+    ///
+    /// Attributes without a type have type `none`.
+    pub fn ty(&self) -> Type<'cx> {
+        unsafe {
+            Type::from_raw(ffi::mlirAttributeGetType(self.inner))
+                .expect("unexpected MLIR error: Attribute type should be non-null")
+        }
+    }
+
+    /// Returns the context in which this attribute was created.
+    #[inline]
+    pub fn context(&self) -> ContextRef<'cx> {
+        unsafe {
+            ContextRef::from_raw(ffi::mlirAttributeGetContext(self.inner))
+                .expect("unexpected MLIR error: Attribute context should be non-null")
+        }
+    }
+}
+
+impl<'cx> From<Identifier<'cx>> for Attribute<'cx> {
     #[inline]
-    fn from(value: Identifier) -> Self {
+    fn from(value: Identifier<'cx>) -> Self {
         // Under the hood, `MlirIdentifier` is actually a `StringAttr`, so it's safe to
         // reinterpret the pointer this way.
         Attribute {
             inner: ffi::MlirAttribute {
                 ptr: value.inner.ptr,
             },
+            phantom: PhantomData,
         }
     }
 }
 
-impl From<Type> for Attribute {
+impl<'cx> From<Type<'cx>> for Attribute<'cx> {
     #[inline]
-    fn from(value: Type) -> Self {
+    fn from(value: Type<'cx>) -> Self {
         TypeAttr::from(value).into()
     }
 }
 
 // Block ======================================================================
 
-impl Block {
+impl<'cx> Block<'cx> {
     #[inline]
-    pub fn create() -> Block {
-        unsafe { Block::from_raw(ffi::mlirBlockCreate(0, ptr::null(), ptr::null())).unwrap() }
+    pub fn create() -> Block<'cx> {
+        Block::try_create().expect("unexpected MLIR error: Block should be non-null")
     }
 
     #[inline]
-    pub fn create_with_args(args: &[Type], locs: &[Location]) -> Block {
-        assert_eq!(
-            args.len(),
-            locs.len(),
-            "block arguments and locations arrays should be the same length"
-        );
+    pub fn try_create() -> Result<Block<'cx>, Error> {
+        unsafe { Block::from_raw(ffi::mlirBlockCreate(0, ptr::null(), ptr::null())) }
+            .ok_or_else(|| Error::null_handle("Block"))
+    }
+
+    /// Destroys this block, or returns it if its values are still used outside of it.
+    ///
+    /// Dropping such a block leaks it silently; use this to detect the leak instead.
+    pub fn try_destroy(self) -> Result<(), Block<'cx>> {
+        let block = ManuallyDrop::new(self);
+
+        let destroyed =
+            unsafe { Scope::Block(block.inner).destroy(|| ffi::mlirBlockDestroy(block.inner)) };
+        if destroyed {
+            Ok(())
+        } else {
+            Err(ManuallyDrop::into_inner(block))
+        }
+    }
+
+    /// Creates a block with arguments of types `args` at locations `locs`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `args` and `locs` have different lengths.
+    #[inline]
+    pub fn create_with_args(args: &[Type<'cx>], locs: &[Location<'cx>]) -> Block<'cx> {
+        Block::try_create_with_args(args, locs).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a block with arguments of types `args` at locations `locs`.
+    ///
+    /// Returns [`Error::InvalidArgument`] if `args` and `locs` have different lengths.
+    #[inline]
+    pub fn try_create_with_args(
+        args: &[Type<'cx>],
+        locs: &[Location<'cx>],
+    ) -> Result<Block<'cx>, Error> {
+        if args.len() != locs.len() {
+            return Err(Error::InvalidArgument(format!(
+                "{} block arguments but {} locations",
+                args.len(),
+                locs.len(),
+            )));
+        }
 
         unsafe {
             Block::from_raw(ffi::mlirBlockCreate(
@@ -433,19 +1026,12 @@ impl Block {
                 args.as_ptr() as *const ffi::MlirType,
                 locs.as_ptr() as *const ffi::MlirLocation,
             ))
-            .unwrap()
-        }
-    }
-
-    #[inline]
-    pub fn add_argument(&mut self, ty: Type, loc: Location) {
-        unsafe {
-            ffi::mlirBlockAddArgument(self.inner, ty.inner, loc.inner);
         }
+        .ok_or_else(|| Error::null_handle("Block"))
     }
 
     #[inline]
-    pub fn terminator(&self) -> Option<OperationRef> {
+    pub fn terminator(&self) -> Option<OperationRef<'cx, '_>> {
         unsafe { OperationRef::from_raw(ffi::mlirBlockGetTerminator(self.inner)) }
     }
 
@@ -453,49 +1039,274 @@ impl Block {
     ///
     /// Returns `None` if this block is unlinked.
     #[inline]
-    pub fn parent_operation(&self) -> Option<OperationRef> {
+    pub fn parent_operation(&self) -> Option<OperationRef<'cx, '_>> {
         unsafe { OperationRef::from_raw(ffi::mlirBlockGetParentOperation(self.inner)) }
     }
-}
 
-impl Deref for BlockRef<'_> {
-    type Target = Block;
+    #[inline]
+    pub fn num_arguments(&self) -> usize {
+        unsafe { ffi::mlirBlockGetNumArguments(self.inner) as usize }
+    }
 
-    fn deref(&self) -> &Self::Target {
-        unsafe { &*(self as *const BlockRef as *const Block) }
+    /// Returns the argument at `index`, or `None` if `index` is out of bounds.
+    #[inline]
+    pub fn argument(&self, index: usize) -> Option<BlockArgument<'cx, '_>> {
+        if index >= self.num_arguments() {
+            return None;
+        }
+
+        unsafe {
+            Some(BlockArgument {
+                inner: Value::from_raw(ffi::mlirBlockGetArgument(self.inner, index as isize))?,
+            })
+        }
+    }
+
+    /// Returns an iterator over the operations in this block.
+    #[inline]
+    pub fn operations(&self) -> Operations<'cx, '_> {
+        Operations {
+            next: unsafe { ffi::mlirBlockGetFirstOperation(self.inner) },
+            phantom: PhantomData,
+        }
     }
 }
 
-impl Deref for BlockMut<'_> {
-    type Target = Block;
+/// Dropping a block destroys it along with the operations within it.
+///
+/// Like an [`Operation`], a block whose values are still used outside of it is leaked instead; see
+/// [`Block::try_destroy`].
+impl Drop for Block<'_> {
+    fn drop(&mut self) {
+        // Blocks linked into a region are only reachable through `BlockRef`/`BlockMut`, so an
+        // owned `Block` is always unlinked.
+        unsafe { Scope::Block(self.inner).destroy(|| ffi::mlirBlockDestroy(self.inner)) };
+    }
+}
 
-    fn deref(&self) -> &Self::Target {
-        unsafe { &*(self as *const BlockMut as *const Block) }
+impl<'cx> BlockMut<'cx, '_> {
+    #[inline]
+    pub fn add_argument(&mut self, ty: Type<'cx>, loc: Location<'cx>) -> BlockArgument<'cx, '_> {
+        unsafe {
+            let arg = ffi::mlirBlockAddArgument(self.inner, ty.inner, loc.inner);
+            BlockArgument {
+                inner: Value::from_raw(arg)
+                    .expect("unexpected MLIR error: block argument should be non-null"),
+            }
+        }
     }
+
+    /// Returns a mutable reference to the argument at `index`, or `None` if `index` is out of
+    /// bounds.
+    #[inline]
+    pub fn argument_mut(&mut self, index: usize) -> Option<ValueMut<'cx, '_>> {
+        if index >= self.num_arguments() {
+            return None;
+        }
+
+        unsafe { ValueMut::from_raw(ffi::mlirBlockGetArgument(self.inner, index as isize)) }
+    }
+
+    /// Appends `op` to the block, transferring ownership of it to the block.
+    #[inline]
+    pub fn append_operation(&mut self, op: Operation<'cx>) -> OperationMut<'cx, '_> {
+        let op = ManuallyDrop::new(op);
+
+        unsafe {
+            ffi::mlirBlockAppendOwnedOperation(self.inner, op.inner);
+            OperationMut {
+                inner: op.inner,
+                phantom: PhantomData,
+            }
+        }
+    }
+
+    /// Returns a mutable reference to the operation at `index`, or `None` if `index` is out of
+    /// bounds.
+    ///
+    /// This walks the block from its first operation, so it takes time linear in `index`. Use an
+    /// [`OperationCursor`](cursor::OperationCursor) to visit or rewrite operations in turn.
+    pub fn operation_mut(&mut self, index: usize) -> Option<OperationMut<'cx, '_>> {
+        let mut op = unsafe { ffi::mlirBlockGetFirstOperation(self.inner) };
+        for _ in 0..index {
+            if op.ptr.is_null() {
+                return None;
+            }
+            op = unsafe { ffi::mlirOperationGetNextInBlock(op) };
+        }
+
+        unsafe { OperationMut::from_raw(op) }
+    }
+
+    /// Inserts `op` before the operation at `index`, transferring ownership of it to the block.
+    ///
+    /// If `index` is equal to the number of operations in the block, appends `op`.
+    ///
+    /// This walks the block from its first operation, so it takes time linear in `index`. Use an
+    /// [`OperationCursor`](cursor::OperationCursor) to insert operations while visiting the block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of operations in the block.
+    pub fn insert_operation_before(
+        &mut self,
+        index: usize,
+        op: Operation<'cx>,
+    ) -> OperationMut<'cx, '_> {
+        let mut reference = unsafe { ffi::mlirBlockGetFirstOperation(self.inner) };
+        for _ in 0..index {
+            assert!(
+                !reference.ptr.is_null(),
+                "insertion index should be at most the number of operations in the block"
+            );
+            reference = unsafe { ffi::mlirOperationGetNextInBlock(reference) };
+        }
+
+        let op = ManuallyDrop::new(op);
+
+        unsafe {
+            // A null reference appends the operation.
+            ffi::mlirBlockInsertOwnedOperationBefore(self.inner, reference, op.inner);
+            OperationMut {
+                inner: op.inner,
+                phantom: PhantomData,
+            }
+        }
+    }
+
+    /// Unlinks this block from its parent region and returns ownership of it.
+    ///
+    /// Returns `None` if this block is unlinked, i.e. if it was borrowed from an owned [`Block`],
+    /// or if it is the body of a `builtin.module`, which must always have exactly one block.
+    ///
+    /// Other operations may also require a fixed number of blocks, but the C API does not expose
+    /// this. Detaching a block from such an operation leaves it invalid until a replacement block
+    /// is inserted.
+    #[inline]
+    pub fn detach(self) -> Option<Block<'cx>> {
+        unsafe {
+            if !is_detachable(self.inner) {
+                return None;
+            }
+
+            ffi::mlirBlockDetach(self.inner);
+            Block::from_raw(self.inner)
+        }
+    }
+}
+
+/// Returns `true` if `block` is linked into a region and may be detached from it.
+///
+/// # Safety
+///
+/// `block` must be valid.
+pub(crate) unsafe fn is_detachable(block: ffi::MlirBlock) -> bool {
+    if ffi::mlirBlockGetParentRegion(block).ptr.is_null() {
+        return false;
+    }
+
+    // `mlirModuleFromOperation` returns null unless the operation is a `builtin.module`.
+    let parent = ffi::mlirBlockGetParentOperation(block);
+    parent.ptr.is_null() || ffi::mlirModuleFromOperation(parent).ptr.is_null()
 }
 
-impl DerefMut for BlockMut<'_> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { &mut *(self as *mut BlockMut as *mut Block) }
+/// An iterator over the operations in a [`Block`].
+pub struct Operations<'cx, 'a> {
+    next: ffi::MlirOperation,
+    phantom: PhantomData<&'a Block<'cx>>,
+}
+
+impl<'cx, 'a> Iterator for Operations<'cx, 'a> {
+    type Item = OperationRef<'cx, 'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let op = unsafe { OperationRef::from_raw(self.next)? };
+        self.next = unsafe { ffi::mlirOperationGetNextInBlock(self.next) };
+        Some(op)
     }
 }
 
+impl std::iter::FusedIterator for Operations<'_, '_> {}
+
+// Context ====================================================================
+
+// A context may be moved between threads once nothing borrows it, but it is not `Sync`: only
+// uniqued object creation is synchronized by MLIR.
+unsafe impl Send for Context {}
+
+thread_local! {
+    static THREAD_GLOBAL_CONTEXT: &'static Context = Box::leak(Box::new(
+        Context::create().expect("unexpected MLIR error: Context should be non-null"),
+    ));
+}
+
 impl Context {
     /// Creates an MLIR context.
-    pub fn create() -> Option<Context> {
-        let ctx = unsafe { ffi::mlirContextCreate() };
+    pub fn create() -> Result<Context, Error> {
+        unsafe { Context::from_raw(ffi::mlirContextCreate()) }
+            .ok_or_else(|| Error::null_handle("Context"))
+    }
 
-        if ctx.ptr.is_null() {
-            return None;
-        }
+    /// Returns a context shared by all callers on the current thread, creating it on first use.
+    ///
+    /// This is an opt-in convenience for programs which only need a single context. Each thread
+    /// gets its own context, which is leaked and never destroyed, so prefer [`Context::create`]
+    /// wherever contexts are created repeatedly or need different settings.
+    pub fn leaked_thread_global() -> &'static Context {
+        THREAD_GLOBAL_CONTEXT.with(|cx| *cx)
+    }
 
-        Some(Context { inner: ctx })
+    /// Creates an MLIR context with multithreading enabled or disabled.
+    pub fn create_with_threading(threading_enabled: bool) -> Result<Context, Error> {
+        unsafe { Context::from_raw(ffi::mlirContextCreateWithThreading(threading_enabled)) }
+            .ok_or_else(|| Error::null_handle("Context"))
     }
 
     /// Registers all dialects in `registry` with this MLIR context.
-    pub fn append_dialect_registry(&mut self, registry: &DialectRegistry) {
+    pub fn append_dialect_registry(&self, registry: &DialectRegistry) {
         unsafe { ffi::mlirContextAppendDialectRegistry(self.inner, registry.inner) }
     }
+
+    /// Enables or disables multithreading within this context.
+    pub fn enable_multithreading(&self, enable: bool) {
+        unsafe { ffi::mlirContextEnableMultithreading(self.inner, enable) }
+    }
+
+    /// Sets whether operations from unregistered dialects may be created in this context.
+    pub fn set_allow_unregistered_dialects(&self, allow: bool) {
+        unsafe { ffi::mlirContextSetAllowUnregisteredDialects(self.inner, allow) }
+    }
+
+    /// Returns `true` if operations from unregistered dialects may be created in this context.
+    pub fn allows_unregistered_dialects(&self) -> bool {
+        unsafe { ffi::mlirContextGetAllowUnregisteredDialects(self.inner) }
+    }
+
+    pub fn num_registered_dialects(&self) -> usize {
+        unsafe { ffi::mlirContextGetNumRegisteredDialects(self.inner) as usize }
+    }
+
+    pub fn num_loaded_dialects(&self) -> usize {
+        unsafe { ffi::mlirContextGetNumLoadedDialects(self.inner) as usize }
+    }
+
+    /// Loads all dialects registered with this context.
+    pub fn load_all_available_dialects(&self) {
+        unsafe { ffi::mlirContextLoadAllAvailableDialects(self.inner) }
+    }
+
+    /// Returns the dialect with the namespace `name`, loading it if it is registered but not yet
+    /// loaded.
+    ///
+    /// Returns `None` if no such dialect is registered.
+    pub fn get_or_load_dialect<'a, S: Into<StringRef<'a>>>(&self, name: S) -> Option<Dialect<'_>> {
+        unsafe {
+            Dialect::from_raw(ffi::mlirContextGetOrLoadDialect(
+                self.inner,
+                name.into().inner,
+            ))
+        }
+    }
 }
 
 impl Drop for Context {
@@ -504,25 +1315,43 @@ impl Drop for Context {
     }
 }
 
+impl Deref for ContextRef<'_> {
+    type Target = Context;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*(self as *const ContextRef as *const Context) }
+    }
+}
+
 // Dialect ====================================================================
 
-impl Dialect {
-    pub fn namespace(&self) -> StringRef {
+impl<'cx> Dialect<'cx> {
+    pub fn namespace(&self) -> StringRef<'cx> {
         unsafe { StringRef::from_raw(ffi::mlirDialectGetNamespace(self.inner)) }
     }
+
+    pub fn context(&self) -> ContextRef<'cx> {
+        unsafe {
+            ContextRef::from_raw(ffi::mlirDialectGetContext(self.inner))
+                .expect("unexpected MLIR error: Dialect context should be non-null")
+        }
+    }
 }
 
 // DialectHandle ==============================================================
 
 impl DialectHandle {
-    pub fn register_dialect(&self) {
-        context().with_mutex(|cx| unsafe { ffi::mlirDialectHandleRegisterDialect(self.inner, cx) })
+    pub fn register_dialect(&self, cx: &Context) {
+        unsafe { ffi::mlirDialectHandleRegisterDialect(self.inner, cx.inner) }
     }
 
-    pub fn load_dialect(&self) -> Option<Dialect> {
-        context().with_mutex(|cx| unsafe {
-            Dialect::from_raw(ffi::mlirDialectHandleLoadDialect(self.inner, cx))
-        })
+    pub fn load_dialect<'cx>(&self, cx: &'cx Context) -> Option<Dialect<'cx>> {
+        unsafe { Dialect::from_raw(ffi::mlirDialectHandleLoadDialect(self.inner, cx.inner)) }
+    }
+
+    /// Adds this dialect to `registry`.
+    pub fn insert_dialect(&self, registry: &mut DialectRegistry) {
+        unsafe { ffi::mlirDialectHandleInsertDialect(self.inner, registry.inner) }
     }
 
     pub fn namespace(&self) -> StringRef {
@@ -542,197 +1371,1033 @@ impl Drop for DialectRegistry {
 }
 
 impl DialectRegistry {
-    pub fn create() -> Option<DialectRegistry> {
-        let reg = unsafe { ffi::mlirDialectRegistryCreate() };
-
-        if reg.ptr.is_null() {
-            return None;
-        }
-
-        Some(DialectRegistry { inner: reg })
+    pub fn create() -> Result<DialectRegistry, Error> {
+        unsafe { DialectRegistry::from_raw(ffi::mlirDialectRegistryCreate()) }
+            .ok_or_else(|| Error::null_handle("DialectRegistry"))
     }
 }
 
 // Identifier =================================================================
 
-impl Identifier {
-    pub fn get<'a, S: Into<StringRef<'a>>>(value: S) -> Identifier {
-        context().without_mutex(|cx| unsafe {
-            Identifier::from_raw(ffi::mlirIdentifierGet(cx, value.into().inner)).unwrap()
-        })
+impl<'cx> Identifier<'cx> {
+    pub fn get<'a, S: Into<StringRef<'a>>>(cx: &'cx Context, value: S) -> Identifier<'cx> {
+        Identifier::try_get(cx, value)
+            .expect("unexpected MLIR error: Identifier should be non-null")
+    }
+
+    pub fn try_get<'a, S: Into<StringRef<'a>>>(
+        cx: &'cx Context,
+        value: S,
+    ) -> Result<Identifier<'cx>, Error> {
+        unsafe { Identifier::from_raw(ffi::mlirIdentifierGet(cx.inner, value.into().inner)) }
+            .ok_or_else(|| Error::null_handle("Identifier"))
     }
 
-    pub fn value(&self) -> StringRef {
+    pub fn value(&self) -> StringRef<'cx> {
         unsafe { StringRef::from_raw(ffi::mlirIdentifierStr(self.inner)) }
     }
+
+    pub fn context(&self) -> ContextRef<'cx> {
+        unsafe {
+            ContextRef::from_raw(ffi::mlirIdentifierGetContext(self.inner))
+                .expect("unexpected MLIR error: Identifier context should be non-null")
+        }
+    }
 }
 
-impl<'a, S> From<S> for Identifier
-where
-    S: Into<StringRef<'a>>,
-{
-    fn from(value: S) -> Self {
-        Identifier::get(value)
+impl fmt::Debug for Identifier<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&String::from_utf8_lossy(self.value().as_bytes()), f)
+    }
+}
+
+// IntegerSet =================================================================
+
+impl<'cx> IntegerSet<'cx> {
+    /// Returns the empty integer set with the given dimensions and symbols.
+    pub fn empty(cx: &'cx Context, num_dims: usize, num_symbols: usize) -> IntegerSet<'cx> {
+        IntegerSet::try_empty(cx, num_dims, num_symbols)
+            .expect("unexpected MLIR error: IntegerSet should be non-null")
+    }
+
+    pub fn try_empty(
+        cx: &'cx Context,
+        num_dims: usize,
+        num_symbols: usize,
+    ) -> Result<IntegerSet<'cx>, Error> {
+        unsafe {
+            IntegerSet::from_raw(ffi::mlirIntegerSetEmptyGet(
+                cx.inner,
+                num_dims as isize,
+                num_symbols as isize,
+            ))
+        }
+        .ok_or_else(|| Error::null_handle("IntegerSet"))
+    }
+
+    pub fn num_dims(&self) -> usize {
+        unsafe { ffi::mlirIntegerSetGetNumDims(self.inner) as usize }
+    }
+
+    pub fn num_symbols(&self) -> usize {
+        unsafe { ffi::mlirIntegerSetGetNumSymbols(self.inner) as usize }
+    }
+
+    pub fn num_constraints(&self) -> usize {
+        unsafe { ffi::mlirIntegerSetGetNumConstraints(self.inner) as usize }
+    }
+
+    pub fn context(&self) -> ContextRef<'cx> {
+        unsafe {
+            ContextRef::from_raw(ffi::mlirIntegerSetGetContext(self.inner))
+                .expect("unexpected MLIR error: IntegerSet context should be non-null")
+        }
     }
 }
 
 // Location ===================================================================
 
-impl Location {
-    pub fn call_site(callee: Location, caller: Location) -> Location {
-        // `UnknownLoc` is cached in `Context`, so it should always be present.
+impl<'cx> Location<'cx> {
+    pub fn call_site(callee: Location<'cx>, caller: Location<'cx>) -> Location<'cx> {
+        Location::try_call_site(callee, caller)
+            .expect("unexpected MLIR error: CallSiteLoc should be non-null")
+    }
+
+    pub fn try_call_site(
+        callee: Location<'cx>,
+        caller: Location<'cx>,
+    ) -> Result<Location<'cx>, Error> {
         unsafe {
             Location::from_raw(ffi::mlirLocationCallSiteGet(
                 callee.as_raw(),
                 caller.as_raw(),
             ))
-            .unwrap()
         }
+        .ok_or_else(|| Error::null_handle("CallSiteLoc"))
     }
 
-    pub fn file_line_col<'a, S: Into<StringRef<'a>>>(filename: S, line: u32, col: u32) -> Location {
+    /// # Panics
+    ///
+    /// Panics if `line` or `col` does not fit in a C `unsigned int`.
+    pub fn file_line_col<'a, S: Into<StringRef<'a>>>(
+        cx: &'cx Context,
+        filename: S,
+        line: u32,
+        col: u32,
+    ) -> Location<'cx> {
+        Location::try_file_line_col(cx, filename, line, col).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Returns [`Error::OutOfRange`] if `line` or `col` does not fit in a C `unsigned int`.
+    pub fn try_file_line_col<'a, S: Into<StringRef<'a>>>(
+        cx: &'cx Context,
+        filename: S,
+        line: u32,
+        col: u32,
+    ) -> Result<Location<'cx>, Error> {
         #![allow(clippy::useless_conversion)]
 
         // These are no-ops on almost every platform, but it's possible for c_uint to be a u16.
-        let line: c_uint = line.try_into().unwrap();
-        let col: c_uint = col.try_into().unwrap();
+        let to_c_uint = |n: u32| {
+            c_uint::try_from(n).map_err(|_| Error::OutOfRange {
+                value: n.to_string(),
+                ty: "c_uint".into(),
+            })
+        };
+        let (line, col) = (to_c_uint(line)?, to_c_uint(col)?);
 
-        // Safety: Locations are uniqued and therefore synchronized.
-        context().without_mutex(|cx| unsafe {
+        unsafe {
             Location::from_raw(ffi::mlirLocationFileLineColGet(
-                cx,
+                cx.inner,
                 filename.into().as_raw(),
                 line,
                 col,
             ))
-            .unwrap()
-        })
+        }
+        .ok_or_else(|| Error::null_handle("FileLineColLoc"))
+    }
+
+    pub fn fused(
+        cx: &'cx Context,
+        locations: &[Location<'cx>],
+        metadata: Attribute<'cx>,
+    ) -> Location<'cx> {
+        Location::try_fused(cx, locations, metadata)
+            .expect("unexpected MLIR error: FusedLoc should be non-null")
     }
 
-    pub fn fused<A: Into<Attribute>>(locations: &[Location], metadata: Attribute) -> Location {
-        // Safety: Locations are uniqued and therefore synchronized.
-        context().without_mutex(|cx| unsafe {
-            // `UnknownLoc` is cached in `Context`, so it should always be present.
+    pub fn try_fused(
+        cx: &'cx Context,
+        locations: &[Location<'cx>],
+        metadata: Attribute<'cx>,
+    ) -> Result<Location<'cx>, Error> {
+        unsafe {
             Location::from_raw(ffi::mlirLocationFusedGet(
-                cx,
+                cx.inner,
                 locations.len() as isize,
                 // Location is #[repr(transparent)] around `MlirLocation`.
                 locations.as_ptr() as *const ffi::MlirLocation,
                 metadata.as_raw(),
             ))
-            .unwrap()
-        })
+        }
+        .ok_or_else(|| Error::null_handle("FusedLoc"))
     }
 
-    pub fn unknown() -> Location {
-        // Safety: Locations are uniqued and therefore synchronized.
-        context().without_mutex(|cx| unsafe {
-            // `UnknownLoc` is cached in `Context`, so it should always be present.
-            Location::from_raw(ffi::mlirLocationUnknownGet(cx))
-                .expect("unexpected MLIR error: UnknownLoc should be non-null")
-        })
+    pub fn unknown(cx: &'cx Context) -> Location<'cx> {
+        // `UnknownLoc` is cached in `Context`, so it should always be present.
+        Location::try_unknown(cx).expect("unexpected MLIR error: UnknownLoc should be non-null")
     }
-}
 
-// Module =====================================================================
+    pub fn try_unknown(cx: &'cx Context) -> Result<Location<'cx>, Error> {
+        unsafe { Location::from_raw(ffi::mlirLocationUnknownGet(cx.inner)) }
+            .ok_or_else(|| Error::null_handle("UnknownLoc"))
+    }
 
-impl Module {
-    pub fn create_empty(location: Location) -> Module {
+    pub fn context(&self) -> ContextRef<'cx> {
         unsafe {
-            Module::from_raw(ffi::mlirModuleCreateEmpty(location.inner))
-                .expect("unexpected MLIR error: empty Module should be non-null")
+            ContextRef::from_raw(ffi::mlirLocationGetContext(self.inner))
+                .expect("unexpected MLIR error: Location context should be non-null")
         }
     }
+}
 
-    pub fn create_parse<'src, S: Into<StringRef<'src>>>(module: S) -> Option<Module> {
-        context().with_mutex(|cx| unsafe {
-            Module::from_raw(ffi::mlirModuleCreateParse(cx, module.into().inner))
-        })
+// Module =====================================================================
+
+impl<'cx> Module<'cx> {
+    pub fn create_empty(location: Location<'cx>) -> Module<'cx> {
+        Module::try_create_empty(location)
+            .expect("unexpected MLIR error: empty Module should be non-null")
     }
 
-    pub fn body(&self) -> Block {
-        unsafe {
-            Block::from_raw(ffi::mlirModuleGetBody(self.inner))
-                .expect("unexpected MLIR error: Module body should be non-null")
-        }
+    pub fn try_create_empty(location: Location<'cx>) -> Result<Module<'cx>, Error> {
+        unsafe { Module::from_raw(ffi::mlirModuleCreateEmpty(location.inner)) }
+            .ok_or_else(|| Error::null_handle("Module"))
     }
-}
 
-// NamedAttribute =============================================================
+    /// Parses a module from its textual form.
+    ///
+    /// If parsing fails, the returned error holds the diagnostics emitted by the parser.
+    pub fn create_parse<'src, S: Into<StringRef<'src>>>(
+        cx: &'cx Context,
+        module: S,
+    ) -> Result<Module<'cx>, Error> {
+        let module = module.into();
+        let (module, diagnostics) = cx.with_diagnostics(|| unsafe {
+            Module::from_raw(ffi::mlirModuleCreateParse(cx.inner, module.inner))
+        });
 
-#[derive(Copy, Clone)]
-#[repr(C)]
-pub struct NamedAttribute {
-    pub name: Identifier,
-    pub attribute: Attribute,
-}
+        module.ok_or(Error::Parse { diagnostics })
+    }
 
-impl NamedAttribute {
-    pub fn get<I, A>(name: I, attribute: A) -> NamedAttribute
+    /// Parses a module from its textual form, using `name` as the source filename in locations
+    /// and diagnostics.
+    ///
+    /// As with [`Module::create_parse`], the top-level operations are wrapped in a
+    /// `builtin.module` unless the source holds a single one.
+    pub fn parse_str_with_name<'src, 'n, S, N>(
+        cx: &'cx Context,
+        src: S,
+        name: N,
+    ) -> Result<Module<'cx>, Error>
     where
-        I: Into<Identifier>,
-        A: Into<Attribute>,
+        S: Into<StringRef<'src>>,
+        N: Into<StringRef<'n>>,
     {
-        NamedAttribute {
-            name: name.into(),
-            attribute: attribute.into(),
-        }
-    }
+        let (src, name) = (src.into(), name.into());
+        let (module, diagnostics) = cx.with_diagnostics(|| unsafe {
+            Module::from_raw(ffi::mlirRsModuleCreateParseWithName(
+                cx.inner, src.inner, name.inner,
+            ))
+        });
 
-    pub(crate) fn as_raw(&self) -> ffi::MlirNamedAttribute {
-        ffi::MlirNamedAttribute {
-            name: self.name.inner,
-            attribute: self.attribute.inner,
-        }
+        module.ok_or(Error::Parse { diagnostics })
     }
-}
 
-// Operation ==================================================================
+    /// Parses a module from the file at `path`.
+    ///
+    /// Locations and diagnostics refer to the file by `path`. Returns [`Error::Io`] if the file
+    /// cannot be read.
+    pub fn parse_file<P: AsRef<Path>>(cx: &'cx Context, path: P) -> Result<Module<'cx>, Error> {
+        let path = path.as_ref();
+        let src = fs::read_to_string(path).map_err(Error::Io)?;
+
+        Module::parse_str_with_name(cx, src.as_str(), &*path.to_string_lossy())
+    }
 
-impl Operation {
-    pub fn create(mut state: OperationState<'_>) -> Option<Operation> {
-        let op = unsafe { ffi::mlirOperationCreate(&mut state.inner as *mut _) };
+    /// Takes ownership of a `builtin.module` operation.
+    ///
+    /// Returns `Err` with the original operation if it is not a `builtin.module`.
+    pub fn from_operation(op: Operation<'cx>) -> Result<Module<'cx>, Operation<'cx>> {
+        let op = ManuallyDrop::new(op);
 
-        if op.ptr.is_null() {
-            return None;
+        match unsafe { Module::from_raw(ffi::mlirModuleFromOperation(op.inner)) } {
+            Some(module) => Ok(module),
+            None => Err(ManuallyDrop::into_inner(op)),
         }
-
-        Some(Operation { inner: op })
     }
-}
 
-impl Deref for OperationRef<'_> {
-    type Target = Operation;
+    /// Releases ownership of the module's underlying `builtin.module` operation.
+    pub fn into_operation(self) -> Operation<'cx> {
+        let module = ManuallyDrop::new(self);
 
-    fn deref(&self) -> &Self::Target {
-        unsafe { &*(self as *const OperationRef as *const Operation) }
+        unsafe {
+            Operation::from_raw(ffi::mlirModuleGetOperation(module.inner))
+                .expect("unexpected MLIR error: Module operation should be non-null")
+        }
     }
-}
-
-impl Deref for OperationMut<'_> {
-    type Target = Operation;
 
-    fn deref(&self) -> &Self::Target {
-        unsafe { &*(self as *const OperationMut as *const Operation) }
+    /// Destroys this module, or returns it if its values are still used outside of it.
+    ///
+    /// Dropping such a module leaks it silently; use this to detect the leak instead.
+    pub fn try_destroy(self) -> Result<(), Module<'cx>> {
+        let module = ManuallyDrop::new(self);
+
+        let destroyed = unsafe {
+            let op = ffi::mlirModuleGetOperation(module.inner);
+            Scope::Operation(op).destroy(|| ffi::mlirModuleDestroy(module.inner))
+        };
+        if destroyed {
+            Ok(())
+        } else {
+            Err(ManuallyDrop::into_inner(module))
+        }
     }
-}
 
-impl DerefMut for OperationMut<'_> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { &mut *(self as *mut OperationMut as *mut Operation) }
+    /// Returns the module's underlying `builtin.module` operation.
+    pub fn as_operation(&self) -> OperationRef<'cx, '_> {
+        unsafe {
+            OperationRef::from_raw(ffi::mlirModuleGetOperation(self.inner))
+                .expect("unexpected MLIR error: Module operation should be non-null")
+        }
     }
-}
 
-// OperationState =============================================================
+    pub fn body(&self) -> BlockRef<'cx, '_> {
+        unsafe {
+            BlockRef::from_raw(ffi::mlirModuleGetBody(self.inner))
+                .expect("unexpected MLIR error: Module body should be non-null")
+        }
+    }
 
-#[repr(transparent)]
-pub struct OperationState<'name> {
-    inner: ffi::MlirOperationState,
-    phantom: PhantomData<&'name ()>,
+    pub fn body_mut(&mut self) -> BlockMut<'cx, '_> {
+        unsafe {
+            BlockMut::from_raw(ffi::mlirModuleGetBody(self.inner))
+                .expect("unexpected MLIR error: Module body should be non-null")
+        }
+    }
+
+    pub fn context(&self) -> ContextRef<'cx> {
+        unsafe {
+            ContextRef::from_raw(ffi::mlirModuleGetContext(self.inner))
+                .expect("unexpected MLIR error: Module context should be non-null")
+        }
+    }
+
+    /// Writes the textual form of the module to `w`.
+    ///
+    /// See [`Operation::write_to`].
+    pub fn write_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        self.as_operation().write_to(w)
+    }
+
+    /// Verifies the module and the operations nested within it.
+    ///
+    /// See [`Operation::verify`].
+    pub fn verify(&self) -> Result<(), Error> {
+        self.as_operation().verify()
+    }
+
+    /// Visits the module operation and every operation nested within it.
+    ///
+    /// See [`Operation::walk`].
+    pub fn walk<'a, F>(&'a self, order: WalkOrder, visitor: F) -> WalkResult
+    where
+        F: FnMut(OperationRef<'cx, 'a>) -> WalkResult,
+    {
+        walk_root(self.as_operation(), order, visitor)
+    }
+
+    /// Visits the operations named `name` within the module.
+    ///
+    /// See [`Operation::walk_filtered`].
+    pub fn walk_filtered<'a, 'n, S, F>(
+        &'a self,
+        order: WalkOrder,
+        name: S,
+        visitor: F,
+    ) -> WalkResult
+    where
+        S: Into<StringRef<'n>>,
+        F: FnMut(OperationRef<'cx, 'a>) -> WalkResult,
+    {
+        walk_root_filtered(self.as_operation(), order, name, visitor)
+    }
+}
+
+/// Like an [`Operation`], a module whose values are still used outside of it is leaked instead of
+/// destroyed; see [`Module::try_destroy`].
+impl Drop for Module<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            let op = ffi::mlirModuleGetOperation(self.inner);
+            Scope::Operation(op).destroy(|| ffi::mlirModuleDestroy(self.inner));
+        }
+    }
+}
+
+// NamedAttribute =============================================================
+
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct NamedAttribute<'cx> {
+    pub name: Identifier<'cx>,
+    pub attribute: Attribute<'cx>,
+}
+
+impl<'cx> NamedAttribute<'cx> {
+    /// Creates a named attribute, interning `name` in the context of `attribute`.
+    pub fn get<'a, S, A>(name: S, attribute: A) -> NamedAttribute<'cx>
+    where
+        S: Into<StringRef<'a>>,
+        A: Into<Attribute<'cx>>,
+    {
+        NamedAttribute::try_get(name, attribute)
+            .expect("unexpected MLIR error: Identifier should be non-null")
+    }
+
+    /// Creates a named attribute, interning `name` in the context of `attribute`.
+    pub fn try_get<'a, S, A>(name: S, attribute: A) -> Result<NamedAttribute<'cx>, Error>
+    where
+        S: Into<StringRef<'a>>,
+        A: Into<Attribute<'cx>>,
+    {
+        let attribute = attribute.into();
+
+        let name = unsafe {
+            let cx = ffi::mlirAttributeGetContext(attribute.inner);
+            Identifier::from_raw(ffi::mlirIdentifierGet(cx, name.into().inner))
+        }
+        .ok_or_else(|| Error::null_handle("Identifier"))?;
+
+        Ok(NamedAttribute { name, attribute })
+    }
+
+    /// Construct a `NamedAttribute` from its C API equivalent.
+    ///
+    /// Returns `None` if either the name or the attribute is null.
+    ///
+    /// # Safety
+    ///
+    /// Calling this constructor must not result in duplicate ownership or mutable aliasing.
+    pub unsafe fn from_raw(raw: ffi::MlirNamedAttribute) -> Option<NamedAttribute<'cx>> {
+        Some(NamedAttribute {
+            name: unsafe { Identifier::from_raw(raw.name)? },
+            attribute: unsafe { Attribute::from_raw(raw.attribute)? },
+        })
+    }
+
+    pub(crate) fn as_raw(&self) -> ffi::MlirNamedAttribute {
+        ffi::MlirNamedAttribute {
+            name: self.name.inner,
+            attribute: self.attribute.inner,
+        }
+    }
+}
+
+// Operation ==================================================================
+
+indexed_iters! {
+    /// An iterator over the operands of an [`Operation`].
+    pub struct Operands<'cx, 'a>: MlirOperation => Value<'cx, 'a> {
+        len: ffi::mlirOperationGetNumOperands,
+        get: ffi::mlirOperationGetOperand,
+    }
+
+    /// An iterator over the results of an [`Operation`].
+    pub struct Results<'cx, 'a>: MlirOperation => Value<'cx, 'a> {
+        len: ffi::mlirOperationGetNumResults,
+        get: ffi::mlirOperationGetResult,
+    }
+
+    /// An iterator over the attributes of an [`Operation`].
+    pub struct Attributes<'cx, 'a>: MlirOperation => NamedAttribute<'cx> {
+        len: ffi::mlirOperationGetNumAttributes,
+        get: ffi::mlirOperationGetAttribute,
+    }
+
+    /// An iterator over the regions of an [`Operation`].
+    pub struct Regions<'cx, 'a>: MlirOperation => RegionRef<'cx, 'a> {
+        len: ffi::mlirOperationGetNumRegions,
+        get: ffi::mlirOperationGetRegion,
+    }
+}
+
+impl<'cx> Operation<'cx> {
+    /// Creates an operation from `state`.
+    ///
+    /// The new operation takes ownership of any regions added to `state`.
+    ///
+    /// If creation fails, the returned error holds the diagnostics emitted by MLIR.
+    ///
+    /// With the `debug-verify` feature enabled, debug builds also verify the new operation and
+    /// return [`Error::Verify`] if it is invalid.
+    pub fn create(mut state: OperationState<'cx, '_, '_>) -> Result<Operation<'cx>, Error> {
+        let cx = unsafe { ffi::mlirLocationGetContext(state.inner.location) };
+        let cx = unsafe { ContextRef::from_raw(cx) }
+            .expect("unexpected MLIR error: Location context should be non-null");
+
+        let (op, diagnostics) = cx.with_diagnostics(|| unsafe {
+            Operation::from_raw(ffi::mlirOperationCreate(&mut state.inner as *mut _))
+        });
+
+        let op = op.ok_or(Error::NullHandle {
+            kind: "Operation",
+            diagnostics,
+        })?;
+
+        #[cfg(all(feature = "debug-verify", debug_assertions))]
+        op.verify()?;
+
+        Ok(op)
+    }
+
+    /// Destroys this operation, or returns it if its values are still used outside of it.
+    ///
+    /// Dropping such an operation leaks it silently; use this to detect the leak instead.
+    pub fn try_destroy(self) -> Result<(), Operation<'cx>> {
+        let op = ManuallyDrop::new(self);
+
+        let destroyed =
+            unsafe { Scope::Operation(op.inner).destroy(|| ffi::mlirOperationDestroy(op.inner)) };
+        if destroyed {
+            Ok(())
+        } else {
+            Err(ManuallyDrop::into_inner(op))
+        }
+    }
+
+    /// Verifies this operation and the operations nested within it.
+    ///
+    /// If verification fails, the returned error holds the diagnostics emitted by the verifier.
+    pub fn verify(&self) -> Result<(), Error> {
+        let (valid, diagnostics) = self
+            .context()
+            .with_diagnostics(|| unsafe { ffi::mlirOperationVerify(self.inner) });
+
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::Verify { diagnostics })
+        }
+    }
+
+    pub fn context(&self) -> ContextRef<'cx> {
+        unsafe {
+            ContextRef::from_raw(ffi::mlirOperationGetContext(self.inner))
+                .expect("unexpected MLIR error: Operation context should be non-null")
+        }
+    }
+
+    /// Returns the fully qualified name of the operation, e.g. `hw.module`.
+    #[inline]
+    pub fn name(&self) -> Identifier<'cx> {
+        unsafe {
+            Identifier::from_raw(ffi::mlirOperationGetName(self.inner))
+                .expect("unexpected MLIR error: Operation name should be non-null")
+        }
+    }
+
+    /// Returns the source location of the operation.
+    #[inline]
+    pub fn location(&self) -> Location<'cx> {
+        unsafe {
+            Location::from_raw(ffi::mlirOperationGetLocation(self.inner))
+                .expect("unexpected MLIR error: Operation location should be non-null")
+        }
+    }
+
+    /// Returns the block containing this operation.
+    ///
+    /// Returns `None` if this operation is unlinked.
+    #[inline]
+    pub fn parent_block(&self) -> Option<BlockRef<'cx, '_>> {
+        unsafe { BlockRef::from_raw(ffi::mlirOperationGetBlock(self.inner)) }
+    }
+
+    /// Returns the closest surrounding operation that contains this operation.
+    ///
+    /// Returns `None` if this operation is unlinked or top-level.
+    #[inline]
+    pub fn parent_operation(&self) -> Option<OperationRef<'cx, '_>> {
+        unsafe { OperationRef::from_raw(ffi::mlirOperationGetParentOperation(self.inner)) }
+    }
+
+    #[inline]
+    pub fn num_operands(&self) -> usize {
+        unsafe { ffi::mlirOperationGetNumOperands(self.inner) as usize }
+    }
+
+    /// Returns the operand at `index`, or `None` if `index` is out of bounds.
+    #[inline]
+    pub fn operand(&self, index: usize) -> Option<Value<'cx, '_>> {
+        if index >= self.num_operands() {
+            return None;
+        }
+
+        unsafe { Value::from_raw(ffi::mlirOperationGetOperand(self.inner, index as isize)) }
+    }
+
+    /// Returns an iterator over the operands of this operation.
+    #[inline]
+    pub fn operands(&self) -> Operands<'cx, '_> {
+        unsafe { Operands::new(self.inner) }
+    }
+
+    #[inline]
+    pub fn num_results(&self) -> usize {
+        unsafe { ffi::mlirOperationGetNumResults(self.inner) as usize }
+    }
+
+    /// Returns the result at `index`, or `None` if `index` is out of bounds.
+    #[inline]
+    pub fn result(&self, index: usize) -> Option<Value<'cx, '_>> {
+        if index >= self.num_results() {
+            return None;
+        }
+
+        unsafe { Value::from_raw(ffi::mlirOperationGetResult(self.inner, index as isize)) }
+    }
+
+    /// Returns an iterator over the results of this operation.
+    #[inline]
+    pub fn results(&self) -> Results<'cx, '_> {
+        unsafe { Results::new(self.inner) }
+    }
+
+    #[inline]
+    pub fn num_attributes(&self) -> usize {
+        unsafe { ffi::mlirOperationGetNumAttributes(self.inner) as usize }
+    }
+
+    /// Returns the attribute named `name`, or `None` if this operation has no such attribute.
+    #[inline]
+    pub fn attribute<'a, S: Into<StringRef<'a>>>(&self, name: S) -> Option<Attribute<'cx>> {
+        unsafe {
+            Attribute::from_raw(ffi::mlirOperationGetAttributeByName(
+                self.inner,
+                name.into().inner,
+            ))
+        }
+    }
+
+    /// Returns an iterator over the attributes of this operation.
+    #[inline]
+    pub fn attributes(&self) -> Attributes<'cx, '_> {
+        unsafe { Attributes::new(self.inner) }
+    }
+
+    #[inline]
+    pub fn num_regions(&self) -> usize {
+        unsafe { ffi::mlirOperationGetNumRegions(self.inner) as usize }
+    }
+
+    /// Returns the region at `index`, or `None` if `index` is out of bounds.
+    #[inline]
+    pub fn region(&self, index: usize) -> Option<RegionRef<'cx, '_>> {
+        if index >= self.num_regions() {
+            return None;
+        }
+
+        unsafe { RegionRef::from_raw(ffi::mlirOperationGetRegion(self.inner, index as isize)) }
+    }
+
+    /// Returns an iterator over the regions of this operation.
+    #[inline]
+    pub fn regions(&self) -> Regions<'cx, '_> {
+        unsafe { Regions::new(self.inner) }
+    }
+}
+
+/// The order in which [`Operation::walk`] visits nested operations.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WalkOrder {
+    /// Visit each operation before the operations nested within it.
+    PreOrder,
+    /// Visit each operation after the operations nested within it.
+    PostOrder,
+}
+
+/// The action a walk visitor requests of [`Operation::walk`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WalkResult {
+    /// Continue the walk.
+    Advance,
+    /// Don't visit the operations nested within the current one.
+    ///
+    /// In a post-order walk, nested operations have already been visited, so this is equivalent
+    /// to `Advance`.
+    Skip,
+    /// Stop the walk.
+    Interrupt,
+}
+
+impl<'cx> Operation<'cx> {
+    /// Visits this operation and every operation nested within it, in the given order.
+    ///
+    /// Returns [`WalkResult::Interrupt`] if the visitor interrupted the walk, and
+    /// [`WalkResult::Advance`] otherwise.
+    pub fn walk<'a, F>(&'a self, order: WalkOrder, visitor: F) -> WalkResult
+    where
+        F: FnMut(OperationRef<'cx, 'a>) -> WalkResult,
+    {
+        walk_root(self.by_ref(), order, visitor)
+    }
+
+    /// Like [`Operation::walk`], but only visits operations named `name`.
+    ///
+    /// Operations with other names are still descended into.
+    pub fn walk_filtered<'a, 'n, S, F>(
+        &'a self,
+        order: WalkOrder,
+        name: S,
+        visitor: F,
+    ) -> WalkResult
+    where
+        S: Into<StringRef<'n>>,
+        F: FnMut(OperationRef<'cx, 'a>) -> WalkResult,
+    {
+        walk_root_filtered(self.by_ref(), order, name, visitor)
+    }
+}
+
+fn walk_root<'cx, 'a, F>(
+    root: OperationRef<'cx, 'a>,
+    order: WalkOrder,
+    mut visitor: F,
+) -> WalkResult
+where
+    F: FnMut(OperationRef<'cx, 'a>) -> WalkResult,
+{
+    if walk_op(root, order, &mut visitor) {
+        WalkResult::Interrupt
+    } else {
+        WalkResult::Advance
+    }
+}
+
+fn walk_root_filtered<'cx, 'a, 'n, S, F>(
+    root: OperationRef<'cx, 'a>,
+    order: WalkOrder,
+    name: S,
+    mut visitor: F,
+) -> WalkResult
+where
+    S: Into<StringRef<'n>>,
+    F: FnMut(OperationRef<'cx, 'a>) -> WalkResult,
+{
+    let name = name.into();
+
+    walk_root(root, order, |op| {
+        if op.name().value().as_bytes() == name.as_bytes() {
+            visitor(op)
+        } else {
+            WalkResult::Advance
+        }
+    })
+}
+
+/// Walks `op` and its nested operations, returning `true` if the walk was interrupted.
+fn walk_op<'cx, 'a>(
+    op: OperationRef<'cx, 'a>,
+    order: WalkOrder,
+    visitor: &mut dyn FnMut(OperationRef<'cx, 'a>) -> WalkResult,
+) -> bool {
+    if order == WalkOrder::PreOrder {
+        match visitor(op) {
+            WalkResult::Advance => (),
+            WalkResult::Skip => return false,
+            WalkResult::Interrupt => return true,
+        }
+    }
+
+    // Safety: the nested IR is borrowed for as long as `op` is.
+    let regions: Regions<'cx, 'a> = unsafe { Regions::new(op.inner) };
+    for region in regions {
+        let blocks: Blocks<'cx, 'a> = Blocks {
+            next: unsafe { ffi::mlirRegionGetFirstBlock(region.inner) },
+            phantom: PhantomData,
+        };
+
+        for block in blocks {
+            let ops: Operations<'cx, 'a> = Operations {
+                next: unsafe { ffi::mlirBlockGetFirstOperation(block.inner) },
+                phantom: PhantomData,
+            };
+
+            for nested in ops {
+                if walk_op(nested, order, visitor) {
+                    return true;
+                }
+            }
+        }
+    }
+
+    order == WalkOrder::PostOrder && visitor(op) == WalkResult::Interrupt
+}
+
+impl Clone for Operation<'_> {
+    /// Creates a deep copy of this operation.
+    ///
+    /// The copy is unlinked, even if this operation is not.
+    fn clone(&self) -> Self {
+        unsafe {
+            Operation::from_raw(ffi::mlirOperationClone(self.inner))
+                .expect("unexpected MLIR error: cloned Operation should be non-null")
+        }
+    }
+}
+
+/// Dropping an operation destroys it along with the IR nested within it.
+///
+/// If a value defined by the operation or nested within it is still used by an operation outside
+/// of it, destroying it would leave that use dangling. Such operations are leaked instead; replace
+/// or erase the remaining uses before dropping the operation to release it. Leaks are silent; use
+/// [`Operation::try_destroy`] to detect them.
+///
+/// Checking for such uses visits every use of every value nested within the operation, and walks
+/// from its owner up to the operation, so dropping takes time proportional to the number of uses
+/// times their nesting depth.
+impl Drop for Operation<'_> {
+    fn drop(&mut self) {
+        // Operations linked into a block are only reachable through `OperationRef`/`OperationMut`,
+        // so an owned `Operation` is always unlinked.
+        unsafe { Scope::Operation(self.inner).destroy(|| ffi::mlirOperationDestroy(self.inner)) };
+    }
+}
+
+// A piece of unlinked IR which is about to be destroyed.
+//
+// The C API has no way to drop the uses of a value, so before destroying IR we check that none of
+// the values defined within it are used from outside of it.
+#[derive(Copy, Clone)]
+enum Scope {
+    Operation(ffi::MlirOperation),
+    Block(ffi::MlirBlock),
+    Region(ffi::MlirRegion),
+}
+
+impl Scope {
+    /// Destroys the scope with `destroy`, unless a value defined within it is used outside of it.
+    ///
+    /// Returns `false` if the scope was left alive. `Drop` impls ignore this, since dropping can't
+    /// return an error and panicking in `drop` would abort when unwinding; the owned wrappers'
+    /// `try_destroy` methods surface it instead.
+    ///
+    /// # Safety
+    ///
+    /// The scope must refer to valid IR, and `destroy` must destroy it.
+    unsafe fn destroy(self, destroy: impl FnOnce()) -> bool {
+        if self.has_external_uses() {
+            return false;
+        }
+
+        destroy();
+        true
+    }
+
+    /// Returns `true` if a value defined within this scope is used outside of it.
+    ///
+    /// # Safety
+    ///
+    /// The scope must refer to valid IR.
+    unsafe fn has_external_uses(self) -> bool {
+        match self {
+            Scope::Operation(op) => self.op_escapes(op),
+            Scope::Block(block) => self.block_escapes(block),
+            Scope::Region(region) => self.region_escapes(region),
+        }
+    }
+
+    unsafe fn op_escapes(self, op: ffi::MlirOperation) -> bool {
+        let results = (0..ffi::mlirOperationGetNumResults(op))
+            .any(|i| self.value_escapes(ffi::mlirOperationGetResult(op, i)));
+
+        results
+            || (0..ffi::mlirOperationGetNumRegions(op))
+                .any(|i| self.region_escapes(ffi::mlirOperationGetRegion(op, i)))
+    }
+
+    unsafe fn region_escapes(self, region: ffi::MlirRegion) -> bool {
+        let mut block = ffi::mlirRegionGetFirstBlock(region);
+        while !block.ptr.is_null() {
+            if self.block_escapes(block) {
+                return true;
+            }
+            block = ffi::mlirBlockGetNextInRegion(block);
+        }
+
+        false
+    }
+
+    unsafe fn block_escapes(self, block: ffi::MlirBlock) -> bool {
+        if (0..ffi::mlirBlockGetNumArguments(block))
+            .any(|i| self.value_escapes(ffi::mlirBlockGetArgument(block, i)))
+        {
+            return true;
+        }
+
+        let mut op = ffi::mlirBlockGetFirstOperation(block);
+        while !op.ptr.is_null() {
+            if self.op_escapes(op) {
+                return true;
+            }
+            op = ffi::mlirOperationGetNextInBlock(op);
+        }
+
+        false
+    }
+
+    unsafe fn value_escapes(self, value: ffi::MlirValue) -> bool {
+        let mut operand = ffi::mlirValueGetFirstUse(value);
+        while !ffi::mlirOpOperandIsNull(operand) {
+            if !self.contains(ffi::mlirOpOperandGetOwner(operand)) {
+                return true;
+            }
+            operand = ffi::mlirOpOperandGetNextUse(operand);
+        }
+
+        false
+    }
+
+    // Returns `true` if `op` is nested within this scope.
+    unsafe fn contains(self, mut op: ffi::MlirOperation) -> bool {
+        loop {
+            if matches!(self, Scope::Operation(root) if root.ptr == op.ptr) {
+                return true;
+            }
+
+            let block = ffi::mlirOperationGetBlock(op);
+            if block.ptr.is_null() {
+                return false;
+            }
+            if matches!(self, Scope::Block(root) if root.ptr == block.ptr) {
+                return true;
+            }
+
+            let region = ffi::mlirBlockGetParentRegion(block);
+            if region.ptr.is_null() {
+                return false;
+            }
+            if matches!(self, Scope::Region(root) if root.ptr == region.ptr) {
+                return true;
+            }
+
+            op = ffi::mlirBlockGetParentOperation(block);
+            if op.ptr.is_null() {
+                return false;
+            }
+        }
+    }
+}
+
+impl<'cx> OperationMut<'cx, '_> {
+    /// Returns a mutable reference to the region at `index`, or `None` if `index` is out of
+    /// bounds.
+    #[inline]
+    pub fn region_mut(&mut self, index: usize) -> Option<RegionMut<'cx, '_>> {
+        if index >= self.num_regions() {
+            return None;
+        }
+
+        unsafe { RegionMut::from_raw(ffi::mlirOperationGetRegion(self.inner, index as isize)) }
+    }
+
+    /// Returns a mutable reference to the result at `index`, or `None` if `index` is out of
+    /// bounds.
+    #[inline]
+    pub fn result_mut(&mut self, index: usize) -> Option<ValueMut<'cx, '_>> {
+        if index >= self.num_results() {
+            return None;
+        }
+
+        unsafe { ValueMut::from_raw(ffi::mlirOperationGetResult(self.inner, index as isize)) }
+    }
+
+    /// Replaces all uses of the results of this operation with `values`, in order.
+    ///
+    /// As `self` borrows the surrounding IR mutably, the replacement values are typically the
+    /// results of a new operation, which is then inserted in place of this one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values does not match the number of results.
+    pub fn replace_all_uses_with(&mut self, values: &[Value<'cx, '_>]) {
+        assert_eq!(
+            values.len(),
+            self.num_results(),
+            "the number of replacement values should match the number of results"
+        );
+
+        for (i, value) in values.iter().enumerate() {
+            unsafe {
+                let result = ffi::mlirOperationGetResult(self.inner, i as isize);
+                ffi::mlirValueReplaceAllUsesOfWith(result, value.inner);
+            }
+        }
+    }
+
+    /// Unlinks this operation from its parent block and returns ownership of it.
+    ///
+    /// Returns `None` if this operation is unlinked, i.e. if it was borrowed from an owned
+    /// [`Operation`].
+    ///
+    /// Values defined by the operation keep their uses. If they are still used outside of the
+    /// operation when it is dropped, it is leaked rather than destroyed.
+    #[inline]
+    pub fn detach(self) -> Option<Operation<'cx>> {
+        unsafe {
+            if ffi::mlirOperationGetBlock(self.inner).ptr.is_null() {
+                return None;
+            }
+
+            ffi::mlirOperationRemoveFromParent(self.inner);
+            Operation::from_raw(self.inner)
+        }
+    }
+}
+
+// OperationState =============================================================
+
+/// The name, location, operands, results, attributes and regions of an operation to create.
+///
+/// Operands borrow the IR defining them as `'v`, so it stays borrowed until the state is passed to
+/// [`Operation::create`]. The defining operation cannot be detached and dropped in the meantime:
+///
+/// ```compile_fail
+/// # use mlir::{cursor::OperationCursor, Context, Location, Module, Operation, OperationState};
+/// let cx = Context::create().unwrap();
+/// cx.set_allow_unregistered_dialects(true);
+/// let mut module = Module::create_parse(&cx, r#"%0 = "test.producer"() : () -> i32"#).unwrap();
+/// let body = module.body();
+/// let producer = body.operations().next().unwrap();
+///
+/// let mut state = OperationState::get("test.consumer", Location::unknown(&cx));
+/// state.add_operands(&[producer.result(0).unwrap()]);
+///
+/// // Error: `module` is still borrowed by the operand.
+/// drop(OperationCursor::front(module.body_mut()).detach());
+/// let consumer = Operation::create(state).unwrap();
+/// ```
+#[repr(transparent)]
+pub struct OperationState<'cx, 'name, 'v> {
+    inner: ffi::MlirOperationState,
+    phantom: PhantomData<(&'cx Context, &'name (), &'v ())>,
 }
 
-impl<'name> OperationState<'name> {
-    pub fn get<S: Into<StringRef<'name>>>(name: S, loc: Location) -> OperationState<'name> {
+impl<'cx, 'name, 'v> OperationState<'cx, 'name, 'v> {
+    pub fn get<S: Into<StringRef<'name>>>(
+        name: S,
+        loc: Location<'cx>,
+    ) -> OperationState<'cx, 'name, 'v> {
         let sref = name.into();
         OperationState {
             inner: unsafe { ffi::mlirOperationStateGet(sref.inner, loc.inner) },
@@ -740,15 +2405,15 @@ impl<'name> OperationState<'name> {
         }
     }
 
-    pub fn add_attribute<I, A>(&mut self, ident: I, attribute: A)
+    pub fn add_attribute<'a, S, A>(&mut self, name: S, attribute: A)
     where
-        I: Into<Identifier>,
-        A: Into<Attribute>,
+        S: Into<StringRef<'a>>,
+        A: Into<Attribute<'cx>>,
     {
-        self.add_attributes(&[NamedAttribute::get(ident.into(), attribute.into())]);
+        self.add_attributes(&[NamedAttribute::get(name, attribute)]);
     }
 
-    pub fn add_attributes(&mut self, attributes: &[NamedAttribute]) {
+    pub fn add_attributes(&mut self, attributes: &[NamedAttribute<'cx>]) {
         unsafe {
             ffi::mlirOperationStateAddAttributes(
                 &mut self.inner as *mut _,
@@ -758,7 +2423,7 @@ impl<'name> OperationState<'name> {
         }
     }
 
-    pub fn add_results(&mut self, results: &[Type]) {
+    pub fn add_results(&mut self, results: &[Type<'cx>]) {
         unsafe {
             ffi::mlirOperationStateAddResults(
                 &mut self.inner as *mut _,
@@ -768,7 +2433,7 @@ impl<'name> OperationState<'name> {
         }
     }
 
-    pub fn add_operands(&mut self, operands: &[Value]) {
+    pub fn add_operands(&mut self, operands: &[Value<'cx, 'v>]) {
         unsafe {
             ffi::mlirOperationStateAddOperands(
                 &mut self.inner as *mut _,
@@ -778,7 +2443,10 @@ impl<'name> OperationState<'name> {
         }
     }
 
-    pub fn add_region(&mut self, region: Region) -> RegionMut {
+    /// Adds a region to the operation, transferring ownership of it to the operation state.
+    ///
+    /// If the state is never passed to [`Operation::create`], the region is leaked.
+    pub fn add_region(&mut self, region: Region<'cx>) -> RegionMut<'cx, '_> {
         // Don't drop the region.
         let region = ManuallyDrop::new(region);
 
@@ -789,13 +2457,19 @@ impl<'name> OperationState<'name> {
                 &region.inner as *const _,
             );
 
-            RegionMut::from_raw(region.inner).unwrap()
+            RegionMut {
+                inner: region.inner,
+                phantom: PhantomData,
+            }
         }
     }
 
-    pub fn add_regions(&mut self, regions: Vec<Region>) {
+    /// Adds regions to the operation, transferring ownership of them to the operation state.
+    ///
+    /// If the state is never passed to [`Operation::create`], the regions are leaked.
+    pub fn add_regions(&mut self, regions: Vec<Region<'cx>>) {
         // Decompose the vector into its raw parts.
-        let ptr: *mut ManuallyDrop<Region> = regions.as_ptr() as *mut _;
+        let ptr: *mut ManuallyDrop<Region<'cx>> = regions.as_ptr() as *mut _;
         let len = regions.len();
         let cap = regions.capacity();
 
@@ -819,43 +2493,102 @@ impl<'name> OperationState<'name> {
 
 // Region =====================================================================
 
-impl Region {
-    pub fn create() -> Region {
-        unsafe { Region::from_raw(ffi::mlirRegionCreate()).unwrap() }
+impl<'cx> Region<'cx> {
+    pub fn create() -> Region<'cx> {
+        Region::try_create().expect("unexpected MLIR error: Region should be non-null")
+    }
+
+    pub fn try_create() -> Result<Region<'cx>, Error> {
+        unsafe { Region::from_raw(ffi::mlirRegionCreate()) }
+            .ok_or_else(|| Error::null_handle("Region"))
+    }
+
+    /// Destroys this region, or returns it if its values are still used outside of it.
+    ///
+    /// Dropping such a region leaks it silently; use this to detect the leak instead.
+    pub fn try_destroy(self) -> Result<(), Region<'cx>> {
+        let region = ManuallyDrop::new(self);
+
+        let destroyed =
+            unsafe { Scope::Region(region.inner).destroy(|| ffi::mlirRegionDestroy(region.inner)) };
+        if destroyed {
+            Ok(())
+        } else {
+            Err(ManuallyDrop::into_inner(region))
+        }
+    }
+
+    /// Returns an iterator over the blocks in this region.
+    #[inline]
+    pub fn blocks(&self) -> Blocks<'cx, '_> {
+        Blocks {
+            next: unsafe { ffi::mlirRegionGetFirstBlock(self.inner) },
+            phantom: PhantomData,
+        }
+    }
+}
+
+/// Dropping a region destroys it along with the blocks within it.
+///
+/// Like an [`Operation`], a region whose values are still used outside of it is leaked instead; see
+/// [`Region::try_destroy`].
+impl Drop for Region<'_> {
+    fn drop(&mut self) {
+        // Regions owned by an operation are only reachable through `RegionRef`/`RegionMut`, so an
+        // owned `Region` is always unlinked.
+        unsafe { Scope::Region(self.inner).destroy(|| ffi::mlirRegionDestroy(self.inner)) };
     }
+}
 
-    pub fn append_block(&mut self, block: Block) -> BlockMut {
+impl<'cx> RegionMut<'cx, '_> {
+    /// Appends `block` to the region, transferring ownership of it to the region.
+    pub fn append_block(&mut self, block: Block<'cx>) -> BlockMut<'cx, '_> {
         let block = ManuallyDrop::new(block);
 
         unsafe {
             ffi::mlirRegionAppendOwnedBlock(self.inner, block.inner);
-            BlockMut::from_raw(block.inner).unwrap()
+            BlockMut {
+                inner: block.inner,
+                phantom: PhantomData,
+            }
         }
     }
-}
 
-impl Deref for RegionRef<'_> {
-    type Target = Region;
+    /// Returns a mutable reference to the block at `index`, or `None` if `index` is out of bounds.
+    ///
+    /// This walks the region from its first block, so it takes time linear in `index`. Use a
+    /// [`BlockCursor`](cursor::BlockCursor) to visit or rewrite blocks in turn.
+    pub fn block_mut(&mut self, index: usize) -> Option<BlockMut<'cx, '_>> {
+        let mut block = unsafe { ffi::mlirRegionGetFirstBlock(self.inner) };
+        for _ in 0..index {
+            if block.ptr.is_null() {
+                return None;
+            }
+            block = unsafe { ffi::mlirBlockGetNextInRegion(block) };
+        }
 
-    fn deref(&self) -> &Self::Target {
-        unsafe { &*(self as *const RegionRef as *const Region) }
+        unsafe { BlockMut::from_raw(block) }
     }
 }
 
-impl Deref for RegionMut<'_> {
-    type Target = Region;
-
-    fn deref(&self) -> &Self::Target {
-        unsafe { &*(self as *const RegionMut as *const Region) }
-    }
+/// An iterator over the blocks in a [`Region`].
+pub struct Blocks<'cx, 'a> {
+    next: ffi::MlirBlock,
+    phantom: PhantomData<&'a Region<'cx>>,
 }
 
-impl DerefMut for RegionMut<'_> {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { &mut *(self as *mut RegionMut as *mut Region) }
+impl<'cx, 'a> Iterator for Blocks<'cx, 'a> {
+    type Item = BlockRef<'cx, 'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block = unsafe { BlockRef::from_raw(self.next)? };
+        self.next = unsafe { ffi::mlirBlockGetNextInRegion(self.next) };
+        Some(block)
     }
 }
 
+impl std::iter::FusedIterator for Blocks<'_, '_> {}
+
 // StringRef ==================================================================
 
 #[derive(Copy, Clone)]
@@ -885,14 +2618,17 @@ impl<'a> From<&'a String> for StringRef<'a> {
 }
 
 impl<'a> StringRef<'a> {
-    pub(crate) unsafe fn from_raw(s: ffi::MlirStringRef) -> StringRef<'a> {
+    /// # Safety
+    ///
+    /// `s` must point to `s.length` bytes which remain valid and unchanged for `'a`.
+    pub unsafe fn from_raw(s: ffi::MlirStringRef) -> StringRef<'a> {
         StringRef {
             inner: s,
             phantom: PhantomData,
         }
     }
 
-    pub(crate) fn as_raw(&self) -> ffi::MlirStringRef {
+    pub fn as_raw(&self) -> ffi::MlirStringRef {
         self.inner
     }
 
@@ -900,14 +2636,14 @@ impl<'a> StringRef<'a> {
         unsafe { slice::from_raw_parts(self.inner.data as *const u8, self.inner.length) }
     }
 
-    pub fn to_str(&self) -> Option<&str> {
-        std::str::from_utf8(self.as_bytes()).ok()
+    pub fn to_str(&self) -> Result<&str, Error> {
+        Ok(std::str::from_utf8(self.as_bytes())?)
     }
 }
 
 // SymbolTable ================================================================
 
-impl SymbolTable {
+impl SymbolTable<'_> {
     pub fn symbol_attribute_name() -> StringRef<'static> {
         unsafe { StringRef::from_raw(ffi::mlirSymbolTableGetSymbolAttributeName()) }
     }
@@ -919,12 +2655,991 @@ impl SymbolTable {
 
 // Type =======================================================================
 
-impl Type {
-    pub fn downcast<T: TypeSubtype>(self) -> Result<T, Self> {
+impl<'cx> Type<'cx> {
+    /// Parses a type from its textual form, e.g. `tensor<4xf32>`.
+    ///
+    /// If parsing fails, the returned error holds the diagnostics emitted by the parser.
+    pub fn parse<'a, S: Into<StringRef<'a>>>(cx: &'cx Context, s: S) -> Result<Type<'cx>, Error> {
+        let s = s.into();
+        let (ty, diagnostics) = cx.with_diagnostics(|| unsafe {
+            Type::from_raw(ffi::mlirTypeParseGet(cx.inner, s.inner))
+        });
+
+        ty.ok_or(Error::Parse { diagnostics })
+    }
+
+    pub fn downcast<T: TypeSubtype<'cx>>(self) -> Result<T, Self> {
         T::downcast_from(self)
     }
+
+    pub fn context(&self) -> ContextRef<'cx> {
+        unsafe {
+            ContextRef::from_raw(ffi::mlirTypeGetContext(self.inner))
+                .expect("unexpected MLIR error: Type context should be non-null")
+        }
+    }
+}
+
+// Value ======================================================================
+
+is_fns! {
+    impl<'cx, 'a> Value<'cx, 'a> {
+        pub fn is_block_argument = ffi::mlirValueIsABlockArgument;
+        pub fn is_op_result = ffi::mlirValueIsAOpResult;
+    }
+}
+
+impl<'cx, 'a> Value<'cx, 'a> {
+    #[inline]
+    pub fn ty(&self) -> Type<'cx> {
+        unsafe {
+            Type::from_raw(ffi::mlirValueGetType(self.inner))
+                .expect("unexpected MLIR error: Value type should be non-null")
+        }
+    }
+
+    /// Returns this value as a block argument, or `None` if it is an operation result.
+    #[inline]
+    pub fn as_block_argument(self) -> Option<BlockArgument<'cx, 'a>> {
+        self.is_block_argument()
+            .then_some(BlockArgument { inner: self })
+    }
+
+    /// Returns this value as an operation result, or `None` if it is a block argument.
+    #[inline]
+    pub fn as_op_result(self) -> Option<OpResult<'cx, 'a>> {
+        self.is_op_result().then_some(OpResult { inner: self })
+    }
+
+    /// Returns an iterator over the uses of this value by operations within the IR borrowed as
+    /// `'a`.
+    ///
+    /// Uses outside of the borrowed IR are filtered out, so unlike MLIR's `getUses`, this does not
+    /// always return every use. For example, an operation which was detached while still using a
+    /// value defined in its old parent is skipped: it is not borrowed by `'a`, so handing it out
+    /// could alias a mutable borrow elsewhere. Use [`Value::has_external_uses`] to detect such
+    /// uses.
+    #[inline]
+    pub fn uses(&self) -> Uses<'cx, 'a> {
+        Uses {
+            next: unsafe { ffi::mlirValueGetFirstUse(self.inner) },
+            root: self.root(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns `true` if the value is used outside of the IR containing it, which
+    /// [`Value::uses`] skips.
+    pub fn has_external_uses(&self) -> bool {
+        let root = self.root();
+        let mut operand = unsafe { ffi::mlirValueGetFirstUse(self.inner) };
+        while !unsafe { ffi::mlirOpOperandIsNull(operand) } {
+            if unsafe { op_root(ffi::mlirOpOperandGetOwner(operand)) } != root {
+                return true;
+            }
+            operand = unsafe { ffi::mlirOpOperandGetNextUse(operand) };
+        }
+
+        false
+    }
+
+    // Returns the outermost object containing the value.
+    fn root(&self) -> *const c_void {
+        unsafe {
+            if self.is_block_argument() {
+                block_root(ffi::mlirBlockArgumentGetOwner(self.inner))
+            } else {
+                op_root(ffi::mlirOpResultGetOwner(self.inner))
+            }
+        }
+    }
+}
+
+impl<'cx> ValueMut<'cx, '_> {
+    /// Borrows this value immutably.
+    #[inline]
+    pub fn by_ref(&self) -> Value<'cx, '_> {
+        Value {
+            inner: self.inner,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Reborrows this `ValueMut` for a shorter lifetime.
+    #[inline]
+    pub fn reborrow(&mut self) -> ValueMut<'cx, '_> {
+        ValueMut {
+            inner: self.inner,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Changes the type of the value in place.
+    ///
+    /// Users of the value are not updated and may need to be adjusted to remain valid.
+    #[inline]
+    pub fn set_type(&mut self, ty: Type<'cx>) {
+        unsafe { ffi::mlirValueSetType(self.inner, ty.inner) }
+    }
+
+    /// Replaces all uses of the value with `with`.
+    ///
+    /// This includes uses outside of the borrowed IR, which [`Value::uses`] skips.
+    #[inline]
+    pub fn replace_all_uses_with(&mut self, with: Value<'cx, '_>) {
+        unsafe { ffi::mlirValueReplaceAllUsesOfWith(self.inner, with.inner) }
+    }
+}
+
+// Returns the outermost object containing `op`, which owns it.
+unsafe fn op_root(op: ffi::MlirOperation) -> *const c_void {
+    let block = ffi::mlirOperationGetBlock(op);
+    if block.ptr.is_null() {
+        return op.ptr as *const c_void;
+    }
+
+    block_root(block)
+}
+
+// Returns the outermost object containing `block`, which owns it.
+unsafe fn block_root(block: ffi::MlirBlock) -> *const c_void {
+    let region = ffi::mlirBlockGetParentRegion(block);
+    if region.ptr.is_null() {
+        return block.ptr as *const c_void;
+    }
+
+    let op = ffi::mlirBlockGetParentOperation(block);
+    if op.ptr.is_null() {
+        return region.ptr as *const c_void;
+    }
+
+    op_root(op)
+}
+
+/// A [`Value`] defined as an argument of a [`Block`].
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct BlockArgument<'cx, 'a> {
+    inner: Value<'cx, 'a>,
+}
+
+impl<'cx, 'a> BlockArgument<'cx, 'a> {
+    /// Returns the block which defines this argument.
+    #[inline]
+    pub fn owner(&self) -> BlockRef<'cx, 'a> {
+        unsafe {
+            BlockRef::from_raw(ffi::mlirBlockArgumentGetOwner(self.inner.inner))
+                .expect("unexpected MLIR error: BlockArgument owner should be non-null")
+        }
+    }
+
+    /// Returns the position of this argument in the argument list of its block.
+    #[inline]
+    pub fn arg_number(&self) -> usize {
+        unsafe { ffi::mlirBlockArgumentGetArgNumber(self.inner.inner) as usize }
+    }
 }
 
+impl<'cx, 'a> Deref for BlockArgument<'cx, 'a> {
+    type Target = Value<'cx, 'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<'cx, 'a> From<BlockArgument<'cx, 'a>> for Value<'cx, 'a> {
+    #[inline]
+    fn from(value: BlockArgument<'cx, 'a>) -> Self {
+        value.inner
+    }
+}
+
+/// A [`Value`] defined as a result of an [`Operation`].
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct OpResult<'cx, 'a> {
+    inner: Value<'cx, 'a>,
+}
+
+impl<'cx, 'a> OpResult<'cx, 'a> {
+    /// Returns the operation which defines this result.
+    #[inline]
+    pub fn owner(&self) -> OperationRef<'cx, 'a> {
+        unsafe {
+            OperationRef::from_raw(ffi::mlirOpResultGetOwner(self.inner.inner))
+                .expect("unexpected MLIR error: OpResult owner should be non-null")
+        }
+    }
+
+    /// Returns the position of this result in the result list of its operation.
+    #[inline]
+    pub fn result_number(&self) -> usize {
+        unsafe { ffi::mlirOpResultGetResultNumber(self.inner.inner) as usize }
+    }
+}
+
+impl<'cx, 'a> Deref for OpResult<'cx, 'a> {
+    type Target = Value<'cx, 'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<'cx, 'a> From<OpResult<'cx, 'a>> for Value<'cx, 'a> {
+    #[inline]
+    fn from(value: OpResult<'cx, 'a>) -> Self {
+        value.inner
+    }
+}
+
+impl<'cx, 'a> OpOperand<'cx, 'a> {
+    /// Returns the operation which uses the value.
+    #[inline]
+    pub fn owner(&self) -> OperationRef<'cx, 'a> {
+        unsafe {
+            OperationRef::from_raw(ffi::mlirOpOperandGetOwner(self.inner))
+                .expect("unexpected MLIR error: OpOperand owner should be non-null")
+        }
+    }
+
+    /// Returns the position of the value in the operand list of the owner.
+    #[inline]
+    pub fn operand_number(&self) -> usize {
+        unsafe { ffi::mlirOpOperandGetOperandNumber(self.inner) as usize }
+    }
+}
+
+// The C API has no equality function for operands, but each operand is a distinct object.
+impl PartialEq for OpOperand<'_, '_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.ptr == other.inner.ptr
+    }
+}
+
+impl Eq for OpOperand<'_, '_> {}
+
+impl fmt::Debug for OpOperand<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OpOperand")
+            .field("owner", &self.owner().name())
+            .field("operand_number", &self.operand_number())
+            .finish()
+    }
+}
+
+/// An iterator over the uses of a [`Value`].
+pub struct Uses<'cx, 'a> {
+    next: ffi::MlirOpOperand,
+    // The object owning the value. Uses by operations it doesn't contain are skipped.
+    root: *const c_void,
+    phantom: PhantomData<&'a Operation<'cx>>,
+}
+
+impl<'cx, 'a> Iterator for Uses<'cx, 'a> {
+    type Item = OpOperand<'cx, 'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if unsafe { ffi::mlirOpOperandIsNull(self.next) } {
+                return None;
+            }
+
+            let operand = unsafe { OpOperand::from_raw(self.next)? };
+            self.next = unsafe { ffi::mlirOpOperandGetNextUse(self.next) };
+
+            if unsafe { op_root(ffi::mlirOpOperandGetOwner(operand.inner)) } == self.root {
+                return Some(operand);
+            }
+        }
+    }
+}
+
+impl std::iter::FusedIterator for Uses<'_, '_> {}
+
 mod private {
     pub trait Sealed {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    use crate::{attr::StringAttr, cursor::OperationCursor, diag::Severity, ty::IntegerType};
+
+    fn context() -> Context {
+        let cx = Context::create().unwrap();
+        cx.set_allow_unregistered_dialects(true);
+        cx
+    }
+
+    const USES: &str = r#"
+        %0 = "test.producer"() : () -> i32
+        "test.consumer"(%0) : (i32) -> ()
+    "#;
+
+    const FUNC: &str = r#"
+        "test.func"() ({
+        ^bb0(%arg0: i32):
+          %0 = "test.add"(%arg0, %arg0) : (i32, i32) -> i32
+          "test.return"(%0) : (i32) -> ()
+        }) : () -> ()
+    "#;
+
+    const PAIR: &str = r#"
+        %0 = "test.source"() : () -> i32
+        %1:2 = "test.pair"(%0) ({
+          "test.a"() : () -> ()
+        }, {
+        }) {flag, count = 3 : i64} : (i32) -> (i32, i64)
+    "#;
+
+    const NESTED: &str = r#"
+        "test.outer"() ({
+          "test.inner"() ({
+            "test.a"() : () -> ()
+          }) : () -> ()
+          "test.b"() : () -> ()
+        }) : () -> ()
+        "test.a"() : () -> ()
+    "#;
+
+    fn name(op: &Operation) -> String {
+        op.name().value().to_str().unwrap().to_owned()
+    }
+
+    #[test]
+    fn block_argument_uses() {
+        let cx = context();
+        let module = Module::create_parse(&cx, FUNC).unwrap();
+        let body = module.body();
+        let func = body.operations().next().unwrap();
+        let region = func.region(0).unwrap();
+        let block = region.blocks().next().unwrap();
+
+        let arg = block.argument(0).unwrap();
+        assert_eq!(arg.ty(), IntegerType::signless(&cx, 32).into());
+        assert_eq!(arg.arg_number(), 0);
+        assert!(*arg.owner() == *block);
+        assert!(arg.as_op_result().is_none());
+
+        let mut uses = arg
+            .uses()
+            .map(|operand| (name(&operand.owner()), operand.operand_number()))
+            .collect::<Vec<_>>();
+        uses.sort();
+        assert_eq!(
+            uses,
+            [("test.add".to_owned(), 0), ("test.add".to_owned(), 1)]
+        );
+    }
+
+    #[test]
+    fn op_result_uses() {
+        let cx = context();
+        let module = Module::create_parse(&cx, FUNC).unwrap();
+        let body = module.body();
+        let func = body.operations().next().unwrap();
+        let region = func.region(0).unwrap();
+        let block = region.blocks().next().unwrap();
+        let add = block.operations().next().unwrap();
+
+        let result = add.result(0).unwrap().as_op_result().unwrap();
+        assert_eq!(result.ty(), IntegerType::signless(&cx, 32).into());
+        assert!(*result.owner() == *add);
+        assert_eq!(result.result_number(), 0);
+
+        let uses = result.uses().collect::<Vec<_>>();
+        assert_eq!(uses.len(), 1);
+        assert_eq!(name(&uses[0].owner()), "test.return");
+        assert!(uses[0].owner().operand(0).unwrap() == *result);
+    }
+
+    #[test]
+    fn replace_all_uses_with() {
+        let cx = context();
+        let mut module = Module::create_parse(&cx, FUNC).unwrap();
+        let i32 = IntegerType::signless(&cx, 32).into();
+
+        let mut state = OperationState::get("test.constant", Location::unknown(&cx));
+        state.add_results(&[i32]);
+        let constant = Operation::create(state).unwrap();
+
+        let mut body = module.body_mut();
+        let mut func = body.operation_mut(0).unwrap();
+        let mut region = func.region_mut(0).unwrap();
+        let mut block = region.block_mut(0).unwrap();
+
+        let mut add = block.operation_mut(0).unwrap();
+        add.replace_all_uses_with(&[constant.result(0).unwrap()]);
+        assert_eq!(add.result(0).unwrap().uses().count(), 0);
+        block.insert_operation_before(0, constant);
+
+        let ret = block.operations().last().unwrap();
+        let operand = ret.operand(0).unwrap().as_op_result().unwrap();
+        assert_eq!(name(&operand.owner()), "test.constant");
+        assert_eq!(operand.uses().count(), 1);
+    }
+
+    #[test]
+    fn set_types() {
+        let cx = context();
+        let mut module = Module::create_parse(&cx, FUNC).unwrap();
+        let i64: Type = IntegerType::signless(&cx, 64).into();
+
+        let mut body = module.body_mut();
+        let mut func = body.operation_mut(0).unwrap();
+        let mut region = func.region_mut(0).unwrap();
+        let mut block = region.block_mut(0).unwrap();
+
+        let mut arg = block.argument_mut(0).unwrap();
+        arg.set_type(i64);
+        assert_eq!(arg.by_ref().ty(), i64);
+        assert!(block.argument_mut(1).is_none());
+
+        let mut add = block.operation_mut(0).unwrap();
+        add.result_mut(0).unwrap().set_type(i64);
+        assert!(add.result_mut(1).is_none());
+
+        assert_eq!(block.argument(0).unwrap().ty(), i64);
+        let add = block.operations().next().unwrap();
+        assert_eq!(add.result(0).unwrap().ty(), i64);
+        let ret = block.operations().last().unwrap();
+        assert_eq!(ret.operand(0).unwrap().ty(), i64);
+    }
+
+    #[test]
+    fn replace_argument_uses() {
+        let cx = context();
+        let mut module = Module::create_parse(&cx, FUNC).unwrap();
+        let i32 = IntegerType::signless(&cx, 32).into();
+
+        let mut state = OperationState::get("test.constant", Location::unknown(&cx));
+        state.add_results(&[i32]);
+        let constant = Operation::create(state).unwrap();
+
+        let mut body = module.body_mut();
+        let mut func = body.operation_mut(0).unwrap();
+        let mut region = func.region_mut(0).unwrap();
+        let mut block = region.block_mut(0).unwrap();
+
+        let mut arg = block.argument_mut(0).unwrap();
+        arg.replace_all_uses_with(constant.result(0).unwrap());
+        assert_eq!(arg.by_ref().uses().count(), 0);
+        block.insert_operation_before(0, constant);
+
+        // Both operands of the add now use the constant.
+        let constant = block.operations().next().unwrap();
+        let uses = constant.result(0).unwrap().uses().collect::<Vec<_>>();
+        assert_eq!(uses.len(), 2);
+        for operand in uses {
+            assert_eq!(name(&operand.owner()), "test.add");
+        }
+    }
+
+    #[test]
+    fn contexts_are_independent() {
+        let (a, b) = (Context::create().unwrap(), Context::create().unwrap());
+        assert!(a != b);
+
+        let ty: Type = IntegerType::signless(&a, 32).into();
+        assert!(*ty.context() == a);
+        assert!(ty != IntegerType::signless(&b, 32).into());
+    }
+
+    #[test]
+    fn contexts_side_by_side() {
+        let (a, b) = (context(), Context::create().unwrap());
+        assert!(a.allows_unregistered_dialects());
+        assert!(!b.allows_unregistered_dialects());
+
+        // Only `a` accepts operations from unregistered dialects.
+        let module_a = Module::create_parse(&a, USES).unwrap();
+        assert!(matches!(
+            Module::create_parse(&b, USES),
+            Err(Error::Parse { .. })
+        ));
+
+        let module_b = Module::create_parse(&b, "module {}").unwrap();
+        assert!(*module_a.context() == a);
+        assert!(*module_b.context() == b);
+        assert_eq!(module_a.body().operations().count(), 2);
+        assert_eq!(module_b.body().operations().count(), 0);
+
+        drop(module_a);
+        drop(a);
+        assert!(module_b.as_operation().verify().is_ok());
+    }
+
+    #[test]
+    fn leaked_thread_global() {
+        let cx = Context::leaked_thread_global();
+        assert!(cx == Context::leaked_thread_global());
+        assert!(*IntegerType::signless(cx, 1).context() == *cx);
+
+        let other = std::thread::spawn(|| Context::leaked_thread_global().as_raw().ptr as usize)
+            .join()
+            .unwrap();
+        assert_ne!(other, cx.as_raw().ptr as usize);
+    }
+
+    #[test]
+    fn detach_block() {
+        let mut region = Region::create();
+        let mut block = region
+            .by_mut()
+            .append_block(Block::create())
+            .detach()
+            .unwrap();
+
+        assert!(region.blocks().next().is_none());
+        assert!(block.by_mut().detach().is_none());
+    }
+
+    #[test]
+    fn detach_module_body() {
+        let cx = context();
+        let mut module = Module::create_parse(&cx, USES).unwrap();
+
+        assert!(module.body_mut().detach().is_none());
+        assert_eq!(module.body().operations().count(), 2);
+    }
+
+    #[test]
+    fn drop_unused_detached_op() {
+        let cx = context();
+        let mut module = Module::create_parse(&cx, USES).unwrap();
+
+        let mut cursor = OperationCursor::back(module.body_mut());
+        let consumer = cursor.detach().unwrap();
+        assert!(!unsafe { Scope::Operation(consumer.inner).has_external_uses() });
+        drop(consumer);
+
+        assert_eq!(module.body().operations().count(), 1);
+    }
+
+    #[test]
+    fn drop_used_detached_op_leaks() {
+        let cx = context();
+        let mut module = Module::create_parse(&cx, USES).unwrap();
+
+        let producer = OperationCursor::front(module.body_mut()).detach().unwrap();
+        assert!(unsafe { Scope::Operation(producer.inner).has_external_uses() });
+        drop(producer);
+
+        // The consumer's operand still refers to the leaked result.
+        let body = module.body();
+        let consumer = body.operations().next().unwrap();
+        assert_eq!(consumer.name().value().to_str().unwrap(), "test.consumer");
+        assert_eq!(
+            consumer.operand(0).unwrap().ty(),
+            IntegerType::signless(&cx, 32).into()
+        );
+    }
+
+    #[test]
+    fn try_destroy_used_detached_op() {
+        let cx = context();
+        let mut module = Module::create_parse(&cx, USES).unwrap();
+
+        let producer = OperationCursor::front(module.body_mut()).detach().unwrap();
+        let producer = producer.try_destroy().unwrap_err();
+
+        let consumer = OperationCursor::front(module.body_mut()).detach().unwrap();
+        assert!(consumer.try_destroy().is_ok());
+        assert!(producer.try_destroy().is_ok());
+        assert!(module.try_destroy().is_ok());
+    }
+
+    #[test]
+    fn external_uses() {
+        let cx = context();
+        let mut module = Module::create_parse(&cx, USES).unwrap();
+        let consumer = OperationCursor::back(module.body_mut()).detach().unwrap();
+
+        // The detached consumer still uses the producer's result, but is outside the module.
+        let body = module.body();
+        let producer = body.operations().next().unwrap();
+        let result = producer.result(0).unwrap();
+        assert_eq!(result.uses().count(), 0);
+        assert!(result.has_external_uses());
+        assert!(consumer.operand(0).unwrap() == result);
+
+        drop(consumer);
+        assert!(!result.has_external_uses());
+    }
+
+    #[test]
+    fn uses_within_dropped_op_are_internal() {
+        let cx = context();
+        let module = Module::create_parse(&cx, USES).unwrap();
+
+        assert!(!unsafe { Scope::Operation(module.as_operation().inner).has_external_uses() });
+        let op = module.into_operation();
+        drop(op);
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        let cx = context();
+        assert_eq!(
+            AffineMap::try_permutation(&cx, &[1, 0, 2])
+                .unwrap()
+                .num_dims(),
+            3
+        );
+        assert!(matches!(
+            AffineMap::try_permutation(&cx, &[1, 1]),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        let i32 = IntegerType::signless(&cx, 32).into();
+        let loc = Location::unknown(&cx);
+        assert!(Block::try_create_with_args(&[i32], &[loc]).is_ok());
+        assert!(matches!(
+            Block::try_create_with_args(&[i32, i32], &[loc]),
+            Err(Error::InvalidArgument(_))
+        ));
+
+        assert!(Location::try_file_line_col(&cx, "a.mlir", 1, 2).is_ok());
+    }
+
+    #[test]
+    fn parse_str_with_name() {
+        let cx = context();
+        let module = Module::parse_str_with_name(&cx, USES, "uses.mlir").unwrap();
+
+        // Both top-level operations are wrapped in the implicit module.
+        let ops: Vec<_> = module.body().operations().collect();
+        assert_eq!(ops.len(), 2);
+        assert_eq!(ops[0].location().to_string(), r#"loc("uses.mlir":2:9)"#);
+        assert_eq!(ops[1].location().to_string(), r#"loc("uses.mlir":3:9)"#);
+
+        assert!(matches!(
+            Module::parse_str_with_name(&cx, "%0 = ", "bad.mlir"),
+            Err(Error::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_file() {
+        let cx = context();
+        let path = std::env::temp_dir().join(format!("mlir-rs-{}-uses.mlir", std::process::id()));
+        std::fs::write(&path, USES).unwrap();
+        let module = Module::parse_file(&cx, &path);
+        std::fs::remove_file(&path).unwrap();
+
+        let module = module.unwrap();
+        let op = module.body().operations().next().unwrap();
+        assert_eq!(
+            op.location().to_string(),
+            format!("loc({:?}:2:9)", path.to_str().unwrap())
+        );
+
+        assert!(matches!(
+            Module::parse_file(&cx, &path),
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::NotFound
+        ));
+    }
+
+    #[test]
+    fn introspection() {
+        let cx = context();
+        let module = Module::create_parse(&cx, PAIR).unwrap();
+        let body = module.body();
+
+        let module_op = body.parent_operation().unwrap();
+        assert_eq!(name(&module_op), "builtin.module");
+        assert!(module_op.parent_block().is_none());
+        assert!(module_op.parent_operation().is_none());
+
+        let mut ops = body.operations();
+        let (source, pair) = (ops.next().unwrap(), ops.next().unwrap());
+        assert!(ops.next().is_none());
+        assert_eq!(name(&source), "test.source");
+        assert_eq!(name(&pair), "test.pair");
+        assert!(*pair.parent_block().unwrap() == *body);
+        assert!(*pair.parent_operation().unwrap() == *module_op);
+
+        assert_eq!(pair.num_operands(), 1);
+        assert!(pair.operand(0).unwrap() == source.result(0).unwrap());
+        assert!(pair.operand(1).is_none());
+        assert_eq!(pair.operands().count(), 1);
+
+        assert_eq!(pair.num_results(), 2);
+        assert!(pair.result(2).is_none());
+        assert!(pair.results().nth(1).unwrap() == pair.result(1).unwrap());
+        assert_eq!(source.num_results(), 1);
+        assert_eq!(source.num_operands(), 0);
+        assert!(source.operand(0).is_none());
+
+        // Attributes are sorted by name.
+        assert_eq!(pair.num_attributes(), 2);
+        let attrs: Vec<_> = pair
+            .attributes()
+            .map(|named| {
+                (
+                    named.name.value().to_str().unwrap().to_owned(),
+                    named.attribute.to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            attrs,
+            [
+                ("count".to_owned(), "3 : i64".to_owned()),
+                ("flag".to_owned(), "unit".to_owned()),
+            ]
+        );
+        assert_eq!(pair.attribute("count").unwrap().to_string(), "3 : i64");
+        assert!(pair.attribute("missing").is_none());
+        assert_eq!(source.num_attributes(), 0);
+
+        assert_eq!(pair.num_regions(), 2);
+        assert!(pair.region(2).is_none());
+        let regions: Vec<_> = pair.regions().collect();
+        assert_eq!(regions.len(), 2);
+        assert!(*regions[1] == *pair.region(1).unwrap());
+        assert_eq!(source.num_regions(), 0);
+        assert!(source.region(0).is_none());
+    }
+
+    /// Walks `module`, returning the space-separated names of the visited operations and the result
+    /// of the walk.
+    fn walk_names(
+        module: &Module,
+        order: WalkOrder,
+        mut visitor: impl FnMut(&str) -> WalkResult,
+    ) -> (String, WalkResult) {
+        let mut names = Vec::new();
+        let result = module.walk(order, |op| {
+            names.push(name(&op));
+            visitor(names.last().unwrap())
+        });
+        (names.join(" "), result)
+    }
+
+    #[test]
+    fn walk_order() {
+        let cx = context();
+        let module = Module::create_parse(&cx, NESTED).unwrap();
+
+        let (pre, result) = walk_names(&module, WalkOrder::PreOrder, |_| WalkResult::Advance);
+        assert_eq!(result, WalkResult::Advance);
+        assert_eq!(
+            pre,
+            "builtin.module test.outer test.inner test.a test.b test.a"
+        );
+
+        let (post, result) = walk_names(&module, WalkOrder::PostOrder, |_| WalkResult::Advance);
+        assert_eq!(result, WalkResult::Advance);
+        assert_eq!(
+            post,
+            "test.a test.inner test.b test.outer test.a builtin.module"
+        );
+
+        // Walking a nested operation only visits it and the operations within it.
+        let body = module.body();
+        let outer = body.operations().next().unwrap();
+        let mut names = Vec::new();
+        outer.walk(WalkOrder::PostOrder, |op| {
+            names.push(name(&op));
+            WalkResult::Advance
+        });
+        assert_eq!(names, ["test.a", "test.inner", "test.b", "test.outer"]);
+    }
+
+    #[test]
+    fn walk_skip() {
+        let cx = context();
+        let module = Module::create_parse(&cx, NESTED).unwrap();
+        let skip_inner = |name: &str| match name {
+            "test.inner" => WalkResult::Skip,
+            _ => WalkResult::Advance,
+        };
+
+        let (pre, result) = walk_names(&module, WalkOrder::PreOrder, skip_inner);
+        assert_eq!(result, WalkResult::Advance);
+        assert_eq!(pre, "builtin.module test.outer test.inner test.b test.a");
+
+        // Nested operations have already been visited, so skipping has no effect.
+        let (post, result) = walk_names(&module, WalkOrder::PostOrder, skip_inner);
+        assert_eq!(result, WalkResult::Advance);
+        assert_eq!(
+            post,
+            "test.a test.inner test.b test.outer test.a builtin.module"
+        );
+    }
+
+    #[test]
+    fn walk_interrupt() {
+        let cx = context();
+        let module = Module::create_parse(&cx, NESTED).unwrap();
+        let interrupt_at_b = |name: &str| match name {
+            "test.b" => WalkResult::Interrupt,
+            _ => WalkResult::Advance,
+        };
+
+        let (pre, result) = walk_names(&module, WalkOrder::PreOrder, interrupt_at_b);
+        assert_eq!(result, WalkResult::Interrupt);
+        assert_eq!(pre, "builtin.module test.outer test.inner test.a test.b");
+
+        let (post, result) = walk_names(&module, WalkOrder::PostOrder, interrupt_at_b);
+        assert_eq!(result, WalkResult::Interrupt);
+        assert_eq!(post, "test.a test.inner test.b");
+    }
+
+    #[test]
+    fn walk_filtered() {
+        let cx = context();
+        let module = Module::create_parse(&cx, NESTED).unwrap();
+
+        // Operations with other names are descended into but not visited.
+        let mut parents = Vec::new();
+        let result = module.walk_filtered(WalkOrder::PreOrder, "test.a", |op| {
+            parents.push(name(&op.parent_operation().unwrap()));
+            WalkResult::Advance
+        });
+        assert_eq!(result, WalkResult::Advance);
+        assert_eq!(parents, ["test.inner", "builtin.module"]);
+
+        let mut visited = 0;
+        let result = module.walk_filtered(WalkOrder::PostOrder, "test.a", |_| {
+            visited += 1;
+            WalkResult::Interrupt
+        });
+        assert_eq!(result, WalkResult::Interrupt);
+        assert_eq!(visited, 1);
+
+        let result = module.walk_filtered(WalkOrder::PreOrder, "test.missing", |_| {
+            panic!("no operation should be visited")
+        });
+        assert_eq!(result, WalkResult::Advance);
+    }
+
+    #[test]
+    fn verify() {
+        let cx = context();
+        let module = Module::create_parse(&cx, PAIR).unwrap();
+        assert!(module.verify().is_ok());
+        assert!(module.body().operations().next().unwrap().verify().is_ok());
+    }
+
+    #[test]
+    fn verify_failure() {
+        let cx = context();
+        let mut module = Module::create_parse(
+            &cx,
+            r#""test.symbol"() {sym_name = "x"} : () -> () loc("symbols.mlir":1:1)"#,
+        )
+        .unwrap();
+
+        // Cloning the symbol into the same symbol table defines it twice.
+        let symbol = Operation::clone(&module.body().operations().next().unwrap());
+        module.body_mut().append_operation(symbol);
+
+        let Err(Error::Verify { diagnostics }) = module.verify() else {
+            panic!("expected verification to fail");
+        };
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].message, "redefinition of symbol named 'x'");
+        assert_eq!(diagnostics[0].location, r#"loc("symbols.mlir":1:1)"#);
+    }
+
+    #[test]
+    fn handles_as_map_keys() {
+        let cx = context();
+        let types: [Type; 3] = [
+            IntegerType::signless(&cx, 8).into(),
+            IntegerType::signless(&cx, 16).into(),
+            IntegerType::signless(&cx, 32).into(),
+        ];
+        let attrs = ["a", "b", "c"].map(|s| StringAttr::new(&cx, s));
+        let names = ["a", "b", "c"].map(|s| Identifier::get(&cx, s));
+
+        // Uniqued handles are equal, and hash and compare equal, if they were created from the same
+        // arguments.
+        let hashed: HashMap<_, _> = types.iter().zip(0..).map(|(&ty, i)| (ty, i)).collect();
+        let ordered: BTreeMap<_, _> = types.iter().zip(0..).map(|(&ty, i)| (ty, i)).collect();
+        for (i, width) in [8, 16, 32].into_iter().enumerate() {
+            let ty: Type = IntegerType::signless(&cx, width).into();
+            assert_eq!(hashed[&ty], i);
+            assert_eq!(ordered[&ty], i);
+        }
+        assert_eq!(ordered.len(), 3);
+
+        let hashed: HashMap<_, _> = attrs.iter().map(|&attr| (attr, attr.value())).collect();
+        let ordered: BTreeMap<_, _> = attrs.iter().map(|&attr| (attr, attr.value())).collect();
+        let b = StringAttr::new(&cx, "b");
+        assert_eq!(hashed[&b].to_str().unwrap(), "b");
+        assert_eq!(ordered[&b].to_str().unwrap(), "b");
+        assert!(!hashed.contains_key(&StringAttr::new(&cx, "d")));
+
+        let attrs: BTreeMap<_, _> = attrs
+            .iter()
+            .map(|&attr| (Attribute::from(attr), ()))
+            .collect();
+        assert!(attrs.contains_key(&Attribute::from(b)));
+
+        let hashed: HashMap<_, _> = names.iter().map(|&name| (name, ())).collect();
+        let ordered: BTreeMap<_, _> = names.iter().map(|&name| (name, ())).collect();
+        assert!(hashed.contains_key(&Identifier::get(&cx, "c")));
+        assert!(ordered.contains_key(&Identifier::get(&cx, "c")));
+        assert!(!ordered.contains_key(&Identifier::get(&cx, "d")));
+    }
+
+    #[test]
+    fn debug_matches_display() {
+        let cx = context();
+        let i32 = IntegerType::signless(&cx, 32);
+        let attr = StringAttr::new(&cx, "s");
+        let map = AffineMap::identity(&cx, 2);
+        let set = IntegerSet::empty(&cx, 1, 0);
+        let loc = Location::unknown(&cx);
+
+        assert_eq!(format!("{i32:?}"), i32.to_string());
+        assert_eq!(format!("{:?}", Type::from(i32)), "i32");
+        assert_eq!(format!("{attr:?}"), attr.to_string());
+        assert_eq!(format!("{:?}", Attribute::from(attr)), r#""s""#);
+        assert_eq!(format!("{map:?}"), map.to_string());
+        assert_eq!(format!("{set:?}"), set.to_string());
+        assert_eq!(format!("{loc:?}"), loc.to_string());
+
+        // Values have no `Display` impl, so `Debug` prints them as MLIR does.
+        let module = Module::create_parse(&cx, USES).unwrap();
+        let body = module.body();
+        let producer = body.operations().next().unwrap();
+        let result = producer.result(0).unwrap();
+        assert!(format!("{result:?}").contains(r#""test.producer"() : () -> i32"#));
+
+        assert_eq!(format!("{:?}", Identifier::get(&cx, "name")), r#""name""#);
+    }
+
+    #[test]
+    fn fmt_lossy() {
+        fn write(out: &mut String, chunks: &[&[u8]]) -> fmt::Result {
+            let mut userdata = FmtUserdata::new(out);
+            for chunk in chunks {
+                let s = ffi::MlirStringRef {
+                    data: chunk.as_ptr() as *const c_char,
+                    length: chunk.len(),
+                };
+                unsafe {
+                    fmt_callback::<String>(
+                        s,
+                        &mut userdata as *mut FmtUserdata<String> as *mut c_void,
+                    )
+                };
+            }
+            userdata.finish()
+        }
+
+        let mut out = String::new();
+        write(&mut out, &[b"a\xc3", b"\xa9b", b"\xffc", b"\xe2\x82"]).unwrap();
+        assert_eq!(out, "a\u{e9}b\u{fffd}c\u{fffd}");
+    }
+}
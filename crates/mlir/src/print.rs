@@ -0,0 +1,318 @@
+//! Configurable printing of MLIR IR.
+
+use std::{
+    ffi::c_void,
+    fmt::{self, Formatter},
+    io,
+    marker::PhantomData,
+};
+
+use crate::{ffi, fmt_callback, io_callback, FmtUserdata, IoUserdata, Module, Operation, Value};
+
+/// Options controlling how operations are printed.
+///
+/// The default options match the `Display` implementation of [`Operation`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PrintOptions {
+    generic_op_form: bool,
+    debug_info: bool,
+    pretty_debug_info: bool,
+    large_elements_limit: Option<usize>,
+    local_scope: bool,
+    assume_verified: bool,
+}
+
+impl PrintOptions {
+    pub fn new() -> PrintOptions {
+        PrintOptions::default()
+    }
+
+    /// Prints operations in the generic form, even if they have a custom assembly format.
+    pub fn generic_op_form(mut self, enable: bool) -> PrintOptions {
+        self.generic_op_form = enable;
+        self
+    }
+
+    /// Prints operation locations.
+    ///
+    /// If `pretty` is `true`, locations are printed inline in a more readable, but not
+    /// parseable, form.
+    pub fn debug_info(mut self, enable: bool, pretty: bool) -> PrintOptions {
+        self.debug_info = enable;
+        self.pretty_debug_info = pretty;
+        self
+    }
+
+    /// Elides elements attributes with more than `limit` elements.
+    pub fn elide_large_elements(mut self, limit: Option<usize>) -> PrintOptions {
+        self.large_elements_limit = limit;
+        self
+    }
+
+    /// Prints the operation as if it were top-level, without the context of its parent.
+    ///
+    /// This is faster when printing a small part of a large module, but value and block names
+    /// may differ from those in the full printout.
+    pub fn local_scope(mut self, enable: bool) -> PrintOptions {
+        self.local_scope = enable;
+        self
+    }
+
+    /// Skips verifying the operation before printing it.
+    ///
+    /// By default, invalid operations are printed in the generic form.
+    pub fn assume_verified(mut self, enable: bool) -> PrintOptions {
+        self.assume_verified = enable;
+        self
+    }
+
+    fn to_flags(self) -> PrintingFlags {
+        let flags = PrintingFlags(unsafe { ffi::mlirOpPrintingFlagsCreate() });
+
+        unsafe {
+            if let Some(limit) = self.large_elements_limit {
+                ffi::mlirOpPrintingFlagsElideLargeElementsAttrs(flags.0, limit as isize);
+            }
+
+            if self.debug_info {
+                ffi::mlirOpPrintingFlagsEnableDebugInfo(flags.0, true, self.pretty_debug_info);
+            }
+
+            if self.generic_op_form {
+                ffi::mlirOpPrintingFlagsPrintGenericOpForm(flags.0);
+            }
+
+            if self.local_scope {
+                ffi::mlirOpPrintingFlagsUseLocalScope(flags.0);
+            }
+
+            if self.assume_verified {
+                ffi::mlirOpPrintingFlagsAssumeVerified(flags.0);
+            }
+        }
+
+        flags
+    }
+}
+
+// Owned `MlirOpPrintingFlags`.
+struct PrintingFlags(ffi::MlirOpPrintingFlags);
+
+impl Drop for PrintingFlags {
+    fn drop(&mut self) {
+        unsafe { ffi::mlirOpPrintingFlagsDestroy(self.0) }
+    }
+}
+
+/// Displays an operation with the given [`PrintOptions`].
+///
+/// Returned by [`Operation::print_with`].
+pub struct OperationPrinter<'a> {
+    op: ffi::MlirOperation,
+    options: PrintOptions,
+    phantom: PhantomData<&'a ()>,
+}
+
+impl fmt::Display for OperationPrinter<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let flags = self.options.to_flags();
+        let mut userdata = FmtUserdata::new(f);
+
+        unsafe {
+            ffi::mlirOperationPrintWithFlags(
+                self.op,
+                flags.0,
+                Some(fmt_callback::<Formatter<'_>>),
+                &mut userdata as *mut FmtUserdata<Formatter<'_>> as *mut c_void,
+            );
+        }
+
+        userdata.finish()
+    }
+}
+
+impl OperationPrinter<'_> {
+    /// Writes the operation to `w` with the given options.
+    ///
+    /// The output is streamed to `w` as it is printed, and need not be valid UTF-8.
+    pub fn write_to<W: io::Write>(&self, w: &mut W) -> io::Result<()> {
+        let flags = self.options.to_flags();
+        let mut userdata = IoUserdata::new(w);
+
+        unsafe {
+            ffi::mlirOperationPrintWithFlags(
+                self.op,
+                flags.0,
+                Some(io_callback::<W>),
+                &mut userdata as *mut IoUserdata<W> as *mut c_void,
+            );
+        }
+
+        userdata.error.map_or(Ok(()), Err)
+    }
+}
+
+/// Displays a value as it would be printed as an operand, e.g. `%0` or `%arg1`.
+///
+/// Returned by [`Value::print_as_operand`].
+pub struct OperandPrinter<'cx, 'a> {
+    value: Value<'cx, 'a>,
+    options: PrintOptions,
+}
+
+impl fmt::Display for OperandPrinter<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let flags = self.options.to_flags();
+        let mut userdata = FmtUserdata::new(f);
+
+        unsafe {
+            let state = ffi::mlirAsmStateCreateForValue(self.value.inner, flags.0);
+
+            ffi::mlirValuePrintAsOperand(
+                self.value.inner,
+                state,
+                Some(fmt_callback::<Formatter<'_>>),
+                &mut userdata as *mut FmtUserdata<Formatter<'_>> as *mut c_void,
+            );
+
+            ffi::mlirAsmStateDestroy(state);
+        }
+
+        userdata.finish()
+    }
+}
+
+impl Operation<'_> {
+    /// Returns an object which displays this operation with the given options.
+    pub fn print_with(&self, options: &PrintOptions) -> OperationPrinter<'_> {
+        OperationPrinter {
+            op: self.inner,
+            options: *options,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl Module<'_> {
+    /// Returns an object which displays the module operation with the given options.
+    pub fn print_with(&self, options: &PrintOptions) -> OperationPrinter<'_> {
+        OperationPrinter {
+            op: self.as_operation().inner,
+            options: *options,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'cx, 'a> Value<'cx, 'a> {
+    /// Returns an object which displays this value as an operand name, e.g. `%0`.
+    pub fn print_as_operand(&self, options: &PrintOptions) -> OperandPrinter<'cx, 'a> {
+        OperandPrinter {
+            value: *self,
+            options: *options,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Context;
+
+    const NESTED: &str = r#"%0 = "test.a"() : () -> i32
+"test.region"() ({
+^bb0(%arg0: i32):
+  %1 = "test.b"(%0, %arg0) : (i32, i32) -> i32
+}) : () -> ()
+"#;
+
+    fn context() -> Context {
+        let cx = Context::create().unwrap();
+        cx.set_allow_unregistered_dialects(true);
+        cx
+    }
+
+    #[test]
+    fn default_options() {
+        let cx = context();
+        let module = Module::create_parse(&cx, NESTED).unwrap();
+        let printed = module.print_with(&PrintOptions::new()).to_string();
+        assert_eq!(printed, module.as_operation().to_string());
+        assert!(printed.starts_with("module {\n"));
+        assert!(!printed.contains("loc("));
+    }
+
+    #[test]
+    fn generic_op_form() {
+        let cx = context();
+        let module = Module::create_parse(&cx, r#""test.op"() : () -> ()"#).unwrap();
+
+        assert_eq!(
+            module
+                .print_with(&PrintOptions::new())
+                .to_string()
+                .trim_end(),
+            "module {\n  \"test.op\"() : () -> ()\n}"
+        );
+        assert_eq!(
+            module
+                .print_with(&PrintOptions::new().generic_op_form(true))
+                .to_string()
+                .trim_end(),
+            "\"builtin.module\"() ({\n  \"test.op\"() : () -> ()\n}) : () -> ()"
+        );
+    }
+
+    #[test]
+    fn debug_info() {
+        let cx = context();
+        let module =
+            Module::create_parse(&cx, r#""test.op"() : () -> () loc("print.mlir":1:1)"#).unwrap();
+        let body = module.body();
+        let op = body.operations().next().unwrap();
+
+        // Locations are printed as `loc(...)`, possibly through an alias defined after the IR.
+        let printed = op
+            .print_with(&PrintOptions::new().debug_info(true, false))
+            .to_string();
+        assert!(printed.starts_with(r#""test.op"() : () -> () loc("#));
+        assert!(printed.contains(r#"loc("print.mlir":1:1)"#));
+
+        let pretty = op
+            .print_with(&PrintOptions::new().debug_info(true, true))
+            .to_string();
+        assert_eq!(
+            pretty.trim_end(),
+            r#""test.op"() : () -> () print.mlir:1:1"#
+        );
+
+        // Disabling debug info ignores `pretty`.
+        let disabled = op
+            .print_with(&PrintOptions::new().debug_info(false, true))
+            .to_string();
+        assert_eq!(disabled, op.to_string());
+    }
+
+    #[test]
+    fn operand_names() {
+        let cx = context();
+        let module = Module::create_parse(&cx, NESTED).unwrap();
+        let body = module.body();
+        let region_op = body.operations().nth(1).unwrap();
+        let region = region_op.region(0).unwrap();
+        let block = region.blocks().next().unwrap();
+        let b = block.operations().next().unwrap();
+
+        let full = PrintOptions::new();
+        let [a, arg] = [0, 1].map(|i| b.operand(i).unwrap());
+        let result = b.result(0).unwrap();
+        assert_eq!(a.print_as_operand(&full).to_string(), "%0");
+        assert_eq!(arg.print_as_operand(&full).to_string(), "%arg0");
+        assert_eq!(result.print_as_operand(&full).to_string(), "%1");
+
+        // Values are numbered from their defining operation rather than the whole module.
+        let local = PrintOptions::new().local_scope(true);
+        assert_eq!(result.print_as_operand(&local).to_string(), "%0");
+        assert_eq!(arg.print_as_operand(&local).to_string(), "%arg0");
+    }
+}
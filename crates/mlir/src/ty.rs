@@ -1,8 +1,10 @@
 //! The MLIR type system.
 
+use std::{fmt, iter::FusedIterator, marker::PhantomData, ops::Deref, ptr};
+
 use mlir_sys as ffi;
 
-use crate::{is_fns, Type};
+use crate::{is_fns, private, Attribute, Context, Error, StringRef, Type};
 
 /// A trait for subtypes of [`Type`].
 ///
@@ -12,18 +14,18 @@ use crate::{is_fns, Type};
 /// - `can_downcast` may return `true` only if the concrete type of `ty` is `Self`.
 /// - `downcast_from` must return the same object passed in, and may only return `Ok` if
 ///   `can_downcast` returned `true` for `ty`.
-pub unsafe trait TypeSubtype: Sized {
+pub unsafe trait TypeSubtype<'cx>: Sized {
     /// Returns `true` if and only if `Self` is the concrete type of `ty`.
-    fn can_downcast(ty: &Type) -> bool;
+    fn can_downcast(ty: &Type<'cx>) -> bool;
 
     /// Downcasts from `Type` to `Self` without checking invariants.
     ///
     /// # Safety
     ///
     /// This function is safe to call if and only if `ty` is the concrete type of `ty`.
-    unsafe fn downcast_from_unchecked(ty: Type) -> Self;
+    unsafe fn downcast_from_unchecked(ty: Type<'cx>) -> Self;
 
-    fn downcast_from(ty: Type) -> Result<Self, Type> {
+    fn downcast_from(ty: Type<'cx>) -> Result<Self, Type<'cx>> {
         if Self::can_downcast(&ty) {
             Ok(unsafe { Self::downcast_from_unchecked(ty) })
         } else {
@@ -39,35 +41,29 @@ macro_rules! ty_types {
         $(
             #[derive(Copy, Clone)]
             #[repr(transparent)]
-            $v struct $name {
+            $v struct $name<'cx> {
                 inner: ffi::MlirType,
+                phantom: PhantomData<&'cx Context>,
             }
 
-            impl From<$name> for Type {
-                fn from(other: $name) -> Type {
-                    Type { inner: other.inner }
+            impl<'cx> From<$name<'cx>> for Type<'cx> {
+                fn from(other: $name<'cx>) -> Type<'cx> {
+                    Type { inner: other.inner, phantom: PhantomData }
                 }
             }
 
-            impl PartialEq for $name {
-                fn eq(&self, other: &Self) -> bool {
-                    Type::from(*self) == Type::from(*other)
-                }
-            }
-
-            impl Eq for $name {}
-
-            impl std::fmt::Display for $name {
-                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                    std::fmt::Display::fmt(&Type::from(*self), f)
-                }
-            }
+            crate::impl_subtype_traits!($name => Type<'_>);
 
-            impl $name {
+            impl<'cx> $name<'cx> {
                 #[allow(dead_code)]
-                pub(crate) unsafe fn from_raw(ty: ffi::MlirType) -> Option<$name> {
+                pub(crate) unsafe fn from_raw(ty: ffi::MlirType) -> Option<$name<'cx>> {
+                    if ty.ptr.is_null() {
+                        return None;
+                    }
+
                     Some($name {
                         inner: ty,
+                        phantom: PhantomData,
                     })
                 }
 
@@ -83,49 +79,1025 @@ macro_rules! ty_types {
 macro_rules! ty_downcast {
     ($($fn_name:ident => $subtype_name:ident),* $(,)?) => {
         $(
-            unsafe impl TypeSubtype for $subtype_name {
-                fn can_downcast(ty: &Type) -> bool {
+            unsafe impl<'cx> TypeSubtype<'cx> for $subtype_name<'cx> {
+                fn can_downcast(ty: &Type<'cx>) -> bool {
                     Type::$fn_name(ty)
                 }
 
-                unsafe fn downcast_from_unchecked(ty: Type) -> Self {
-                    Self::from_raw(ty.as_raw()).unwrap()
+                unsafe fn downcast_from_unchecked(ty: Type<'cx>) -> Self {
+                    $subtype_name {
+                        inner: ty.as_raw(),
+                        phantom: PhantomData,
+                    }
+                }
+            }
+
+            impl<'cx> TryFrom<Type<'cx>> for $subtype_name<'cx> {
+                type Error = Error;
+
+                fn try_from(ty: Type<'cx>) -> Result<Self, Error> {
+                    ty.downcast().map_err(|ty: Type<'cx>| Error::TypeMismatch {
+                        expected: stringify!($subtype_name),
+                        found: ty.to_string(),
+                    })
+                }
+            }
+
+            impl<'cx> $subtype_name<'cx> {
+                /// Parses a type from its textual form, checking that it is of this kind.
+                pub fn parse<'a, S: Into<StringRef<'a>>>(
+                    cx: &'cx Context,
+                    s: S,
+                ) -> Result<Self, Error> {
+                    Type::parse(cx, s)?.try_into()
                 }
             }
         )*
     };
 }
 
+// Defines constructors which take a context and return a non-null type.
+macro_rules! ty_ctors {
+    ($(
+        $v:vis fn $name:ident::$fn_name:ident / $try_fn_name:ident($(
+            $arg:ident : $arg_ty:ty
+        ),*) = $ctor_fn:path;
+    )*) => {
+        $(
+            impl<'cx> $name<'cx> {
+                $v fn $fn_name(cx: &'cx Context, $($arg: $arg_ty),*) -> $name<'cx> {
+                    $name::$try_fn_name(cx, $($arg),*).expect(concat!(
+                        "unexpected MLIR error: ",
+                        stringify!($name),
+                        " should be non-null"
+                    ))
+                }
+
+                $v fn $try_fn_name(
+                    cx: &'cx Context,
+                    $($arg: $arg_ty),*
+                ) -> Result<$name<'cx>, Error> {
+                    unsafe { $name::from_raw($ctor_fn(cx.as_raw(), $($arg),*)) }
+                        .ok_or_else(|| Error::null_handle(stringify!($name)))
+                }
+            }
+        )*
+    };
+}
+
+// Calls a `*GetChecked` type constructor with an unknown location in the context of `ty`,
+// capturing the diagnostics it emits if the type is invalid.
+fn get_checked<'cx, T, F>(kind: &'static str, ty: Type<'cx>, f: F) -> Result<T, Error>
+where
+    T: TypeSubtype<'cx>,
+    F: FnOnce(ffi::MlirLocation) -> ffi::MlirType,
+{
+    let cx = ty.context();
+    let (raw, diagnostics) =
+        cx.with_diagnostics(|| f(unsafe { ffi::mlirLocationUnknownGet(cx.as_raw()) }));
+
+    match unsafe { Type::from_raw(raw) } {
+        Some(ty) => Ok(unsafe { T::downcast_from_unchecked(ty) }),
+        None => Err(Error::NullHandle { kind, diagnostics }),
+    }
+}
+
+fn raw_shape(shape: &[Dim]) -> Result<Vec<i64>, Error> {
+    shape.iter().map(|dim| dim.to_raw()).collect()
+}
+
+fn null_attr() -> ffi::MlirAttribute {
+    ffi::MlirAttribute { ptr: ptr::null() }
+}
+
 ty_types! {
+    pub struct IntegerType;
+    pub struct IndexType;
+    pub struct NoneType;
+    pub struct FloatType;
+    pub struct Float4E2M1FNType;
+    pub struct Float6E2M3FNType;
+    pub struct Float6E3M2FNType;
+    pub struct Float8E5M2Type;
+    pub struct Float8E4M3Type;
+    pub struct Float8E4M3FNType;
+    pub struct Float8E5M2FNUZType;
+    pub struct Float8E4M3FNUZType;
+    pub struct Float8E4M3B11FNUZType;
+    pub struct Float8E3M4Type;
+    pub struct Float8E8M0FNUType;
     pub struct BF16Type;
     pub struct F16Type;
+    pub struct TF32Type;
     pub struct F32Type;
     pub struct F64Type;
+    pub struct ComplexType;
+    pub struct TupleType;
+    pub struct VectorType;
+    pub struct RankedTensorType;
+    pub struct UnrankedTensorType;
+    pub struct MemRefType;
+    pub struct UnrankedMemRefType;
+    pub struct OpaqueType;
     pub struct FunctionType;
 }
 
 is_fns! {
-    impl Type {
+    impl<'cx> Type<'cx> {
+        pub fn is_integer = ffi::mlirTypeIsAInteger;
+        pub fn is_index = ffi::mlirTypeIsAIndex;
+        pub fn is_none = ffi::mlirTypeIsANone;
+        pub fn is_float = ffi::mlirTypeIsAFloat;
+        pub fn is_float4_e2m1fn = ffi::mlirTypeIsAFloat4E2M1FN;
+        pub fn is_float6_e2m3fn = ffi::mlirTypeIsAFloat6E2M3FN;
+        pub fn is_float6_e3m2fn = ffi::mlirTypeIsAFloat6E3M2FN;
+        pub fn is_float8_e5m2 = ffi::mlirTypeIsAFloat8E5M2;
+        pub fn is_float8_e4m3 = ffi::mlirTypeIsAFloat8E4M3;
+        pub fn is_float8_e4m3fn = ffi::mlirTypeIsAFloat8E4M3FN;
+        pub fn is_float8_e5m2fnuz = ffi::mlirTypeIsAFloat8E5M2FNUZ;
+        pub fn is_float8_e4m3fnuz = ffi::mlirTypeIsAFloat8E4M3FNUZ;
+        pub fn is_float8_e4m3b11fnuz = ffi::mlirTypeIsAFloat8E4M3B11FNUZ;
+        pub fn is_float8_e3m4 = ffi::mlirTypeIsAFloat8E3M4;
+        pub fn is_float8_e8m0fnu = ffi::mlirTypeIsAFloat8E8M0FNU;
+        pub fn is_bf16 = ffi::mlirTypeIsABF16;
+        pub fn is_f16 = ffi::mlirTypeIsAF16;
+        pub fn is_tf32 = ffi::mlirTypeIsATF32;
+        pub fn is_f32 = ffi::mlirTypeIsAF32;
+        pub fn is_f64 = ffi::mlirTypeIsAF64;
+        pub fn is_complex = ffi::mlirTypeIsAComplex;
+        pub fn is_tuple = ffi::mlirTypeIsATuple;
+        pub fn is_vector = ffi::mlirTypeIsAVector;
+        pub fn is_ranked_tensor = ffi::mlirTypeIsARankedTensor;
+        pub fn is_unranked_tensor = ffi::mlirTypeIsAUnrankedTensor;
+        pub fn is_memref = ffi::mlirTypeIsAMemRef;
+        pub fn is_unranked_memref = ffi::mlirTypeIsAUnrankedMemRef;
+        pub fn is_opaque = ffi::mlirTypeIsAOpaque;
         pub fn is_function = ffi::mlirTypeIsAFunction;
     }
 }
 
 ty_downcast! {
+    is_integer => IntegerType,
+    is_index => IndexType,
+    is_none => NoneType,
+    is_float => FloatType,
+    is_float4_e2m1fn => Float4E2M1FNType,
+    is_float6_e2m3fn => Float6E2M3FNType,
+    is_float6_e3m2fn => Float6E3M2FNType,
+    is_float8_e5m2 => Float8E5M2Type,
+    is_float8_e4m3 => Float8E4M3Type,
+    is_float8_e4m3fn => Float8E4M3FNType,
+    is_float8_e5m2fnuz => Float8E5M2FNUZType,
+    is_float8_e4m3fnuz => Float8E4M3FNUZType,
+    is_float8_e4m3b11fnuz => Float8E4M3B11FNUZType,
+    is_float8_e3m4 => Float8E3M4Type,
+    is_float8_e8m0fnu => Float8E8M0FNUType,
+    is_bf16 => BF16Type,
+    is_f16 => F16Type,
+    is_tf32 => TF32Type,
+    is_f32 => F32Type,
+    is_f64 => F64Type,
+    is_complex => ComplexType,
+    is_tuple => TupleType,
+    is_vector => VectorType,
+    is_ranked_tensor => RankedTensorType,
+    is_unranked_tensor => UnrankedTensorType,
+    is_memref => MemRefType,
+    is_unranked_memref => UnrankedMemRefType,
+    is_opaque => OpaqueType,
     is_function => FunctionType,
 }
 
-impl FunctionType {
-    pub fn get(inputs: &[Type], results: &[Type]) -> FunctionType {
-        crate::context().without_mutex(|cx| unsafe {
+ty_ctors! {
+    pub fn IntegerType::signless / try_signless(width: u32) = ffi::mlirIntegerTypeGet;
+    pub fn IntegerType::signed / try_signed(width: u32) = ffi::mlirIntegerTypeSignedGet;
+    pub fn IntegerType::unsigned / try_unsigned(width: u32) = ffi::mlirIntegerTypeUnsignedGet;
+    pub fn IndexType::get / try_get() = ffi::mlirIndexTypeGet;
+    pub fn NoneType::get / try_get() = ffi::mlirNoneTypeGet;
+    pub fn Float4E2M1FNType::get / try_get() = ffi::mlirFloat4E2M1FNTypeGet;
+    pub fn Float6E2M3FNType::get / try_get() = ffi::mlirFloat6E2M3FNTypeGet;
+    pub fn Float6E3M2FNType::get / try_get() = ffi::mlirFloat6E3M2FNTypeGet;
+    pub fn Float8E5M2Type::get / try_get() = ffi::mlirFloat8E5M2TypeGet;
+    pub fn Float8E4M3Type::get / try_get() = ffi::mlirFloat8E4M3TypeGet;
+    pub fn Float8E4M3FNType::get / try_get() = ffi::mlirFloat8E4M3FNTypeGet;
+    pub fn Float8E5M2FNUZType::get / try_get() = ffi::mlirFloat8E5M2FNUZTypeGet;
+    pub fn Float8E4M3FNUZType::get / try_get() = ffi::mlirFloat8E4M3FNUZTypeGet;
+    pub fn Float8E4M3B11FNUZType::get / try_get() = ffi::mlirFloat8E4M3B11FNUZTypeGet;
+    pub fn Float8E3M4Type::get / try_get() = ffi::mlirFloat8E3M4TypeGet;
+    pub fn Float8E8M0FNUType::get / try_get() = ffi::mlirFloat8E8M0FNUTypeGet;
+    pub fn BF16Type::get / try_get() = ffi::mlirBF16TypeGet;
+    pub fn F16Type::get / try_get() = ffi::mlirF16TypeGet;
+    pub fn TF32Type::get / try_get() = ffi::mlirTF32TypeGet;
+    pub fn F32Type::get / try_get() = ffi::mlirF32TypeGet;
+    pub fn F64Type::get / try_get() = ffi::mlirF64TypeGet;
+}
+
+impl<'cx> IntegerType<'cx> {
+    /// Returns the bit width of the type.
+    pub fn width(&self) -> u32 {
+        unsafe { ffi::mlirIntegerTypeGetWidth(self.inner) }
+    }
+
+    /// Returns `true` if the type has no signedness semantics, e.g. `i32`.
+    pub fn is_signless(&self) -> bool {
+        unsafe { ffi::mlirIntegerTypeIsSignless(self.inner) }
+    }
+
+    /// Returns `true` if the type is signed, e.g. `si32`.
+    pub fn is_signed(&self) -> bool {
+        unsafe { ffi::mlirIntegerTypeIsSigned(self.inner) }
+    }
+
+    /// Returns `true` if the type is unsigned, e.g. `ui32`.
+    pub fn is_unsigned(&self) -> bool {
+        unsafe { ffi::mlirIntegerTypeIsUnsigned(self.inner) }
+    }
+}
+
+impl<'cx> FloatType<'cx> {
+    /// Returns the bit width of the type.
+    pub fn width(&self) -> u32 {
+        unsafe { ffi::mlirFloatTypeGetWidth(self.inner) }
+    }
+}
+
+/// A dimension of a [`Shape`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Dim {
+    /// A dimension with a size known at compile time.
+    Static(u64),
+    /// A dimension whose size is only known at runtime, printed as `?`.
+    Dynamic,
+}
+
+impl Dim {
+    fn from_raw(raw: i64) -> Dim {
+        if unsafe { ffi::mlirShapedTypeIsDynamicSize(raw) } {
+            Dim::Dynamic
+        } else {
+            Dim::Static(raw as u64)
+        }
+    }
+
+    // MLIR stores sizes as `i64`, so larger sizes are rejected rather than wrapping around.
+    fn to_raw(self) -> Result<i64, Error> {
+        match self {
+            Dim::Static(size) => i64::try_from(size).map_err(|_| Error::OutOfRange {
+                value: size.to_string(),
+                ty: "a dimension size".into(),
+            }),
+            Dim::Dynamic => Ok(unsafe { ffi::mlirShapedTypeGetDynamicSize() }),
+        }
+    }
+
+    /// Returns the size of the dimension, or `None` if it is dynamic.
+    pub fn size(self) -> Option<u64> {
+        match self {
+            Dim::Static(size) => Some(size),
+            Dim::Dynamic => None,
+        }
+    }
+
+    pub fn is_dynamic(self) -> bool {
+        self == Dim::Dynamic
+    }
+}
+
+impl From<u64> for Dim {
+    fn from(size: u64) -> Dim {
+        Dim::Static(size)
+    }
+}
+
+impl fmt::Display for Dim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dim::Static(size) => write!(f, "{size}"),
+            Dim::Dynamic => f.write_str("?"),
+        }
+    }
+}
+
+/// The dimensions of a ranked shaped type.
+///
+/// Dereferences to a slice of [`Dim`]s, and is displayed in MLIR syntax, e.g. `4x?x8`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Shape {
+    dims: Vec<Dim>,
+}
+
+impl Shape {
+    pub fn rank(&self) -> usize {
+        self.dims.len()
+    }
+
+    /// Returns `true` if no dimension is dynamic.
+    pub fn is_static(&self) -> bool {
+        self.dims.iter().all(|dim| !dim.is_dynamic())
+    }
+
+    /// Returns the number of elements in the shape, or `None` if any dimension is dynamic or the
+    /// number does not fit in a `u64`.
+    pub fn num_elements(&self) -> Option<u64> {
+        self.dims
+            .iter()
+            .try_fold(1u64, |acc, dim| acc.checked_mul(dim.size()?))
+    }
+}
+
+impl Deref for Shape {
+    type Target = [Dim];
+
+    fn deref(&self) -> &[Dim] {
+        &self.dims
+    }
+}
+
+impl From<Vec<Dim>> for Shape {
+    fn from(dims: Vec<Dim>) -> Shape {
+        Shape { dims }
+    }
+}
+
+impl From<&[Dim]> for Shape {
+    fn from(dims: &[Dim]) -> Shape {
+        Shape {
+            dims: dims.to_vec(),
+        }
+    }
+}
+
+impl From<&[u64]> for Shape {
+    fn from(sizes: &[u64]) -> Shape {
+        sizes.iter().copied().map(Dim::Static).collect()
+    }
+}
+
+impl FromIterator<Dim> for Shape {
+    fn from_iter<I: IntoIterator<Item = Dim>>(iter: I) -> Shape {
+        Shape {
+            dims: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, dim) in self.dims.iter().enumerate() {
+            if i > 0 {
+                f.write_str("x")?;
+            }
+
+            write!(f, "{dim}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A trait for types with an element type and an optional shape: vectors, tensors and memrefs.
+///
+/// This trait is sealed and cannot be implemented outside of this crate.
+pub trait ShapedType<'cx>: TypeSubtype<'cx> + Copy + Into<Type<'cx>> + private::Sealed {
+    fn element_type(&self) -> Type<'cx> {
+        unsafe {
+            Type::from_raw(ffi::mlirShapedTypeGetElementType(raw_ty(*self)))
+                .expect("unexpected MLIR error: shaped type element type should be non-null")
+        }
+    }
+
+    /// Returns the number of dimensions, or `None` if the type is unranked.
+    fn rank(&self) -> Option<usize> {
+        let raw = raw_ty(*self);
+
+        unsafe { ffi::mlirShapedTypeHasRank(raw) }
+            .then(|| unsafe { ffi::mlirShapedTypeGetRank(raw) } as usize)
+    }
+
+    /// Returns the shape, or `None` if the type is unranked.
+    fn shape(&self) -> Option<Shape> {
+        let raw = raw_ty(*self);
+        let rank = self.rank()?;
+
+        Some(
+            (0..rank)
+                .map(|i| Dim::from_raw(unsafe { ffi::mlirShapedTypeGetDimSize(raw, i as isize) }))
+                .collect(),
+        )
+    }
+
+    /// Returns dimension `index`, or `None` if the type is unranked or `index` is out of bounds.
+    fn dim(&self, index: usize) -> Option<Dim> {
+        if index >= self.rank()? {
+            return None;
+        }
+
+        Some(Dim::from_raw(unsafe {
+            ffi::mlirShapedTypeGetDimSize(raw_ty(*self), index as isize)
+        }))
+    }
+
+    /// Returns the number of elements, or `None` if the shape is not static or the number does
+    /// not fit in a `u64`.
+    fn num_elements(&self) -> Option<u64> {
+        self.shape()?.num_elements()
+    }
+
+    /// Returns `true` if the type is ranked and has no dynamic dimensions.
+    fn has_static_shape(&self) -> bool {
+        unsafe { ffi::mlirShapedTypeHasStaticShape(raw_ty(*self)) }
+    }
+}
+
+fn raw_ty<'cx, T: Into<Type<'cx>>>(ty: T) -> ffi::MlirType {
+    ty.into().as_raw()
+}
+
+macro_rules! shaped_types {
+    ($($name:ident),* $(,)?) => {
+        $(
+            impl private::Sealed for $name<'_> {}
+
+            impl<'cx> ShapedType<'cx> for $name<'cx> {}
+        )*
+    };
+}
+
+shaped_types! {
+    VectorType,
+    RankedTensorType,
+    UnrankedTensorType,
+    MemRefType,
+    UnrankedMemRefType,
+}
+
+impl<'cx> ComplexType<'cx> {
+    /// Creates a complex type with the given element type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `element` is not an integer or float type. See [`ComplexType::try_get`].
+    pub fn get(element: Type<'cx>) -> ComplexType<'cx> {
+        ComplexType::try_get(element).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a complex type with the given element type.
+    ///
+    /// Returns [`Error::TypeMismatch`] if `element` is not an integer or float type.
+    pub fn try_get(element: Type<'cx>) -> Result<ComplexType<'cx>, Error> {
+        if !element.is_integer() && !element.is_float() {
+            return Err(Error::TypeMismatch {
+                expected: "integer or float type",
+                found: element.to_string(),
+            });
+        }
+
+        unsafe { ComplexType::from_raw(ffi::mlirComplexTypeGet(element.as_raw())) }
+            .ok_or_else(|| Error::null_handle("ComplexType"))
+    }
+
+    pub fn element_type(&self) -> Type<'cx> {
+        unsafe {
+            Type::from_raw(ffi::mlirComplexTypeGetElementType(self.inner))
+                .expect("unexpected MLIR error: ComplexType element type should be non-null")
+        }
+    }
+}
+
+impl<'cx> TupleType<'cx> {
+    pub fn get(cx: &'cx Context, elements: &[Type<'cx>]) -> TupleType<'cx> {
+        TupleType::try_get(cx, elements)
+            .expect("unexpected MLIR error: TupleType should be non-null")
+    }
+
+    pub fn try_get(cx: &'cx Context, elements: &[Type<'cx>]) -> Result<TupleType<'cx>, Error> {
+        unsafe {
+            TupleType::from_raw(ffi::mlirTupleTypeGet(
+                cx.as_raw(),
+                elements.len() as isize,
+                elements.as_ptr().cast(),
+            ))
+        }
+        .ok_or_else(|| Error::null_handle("TupleType"))
+    }
+
+    pub fn num_types(&self) -> usize {
+        unsafe { ffi::mlirTupleTypeGetNumTypes(self.inner) as usize }
+    }
+
+    /// Returns the element type at `index`, or `None` if `index` is out of bounds.
+    pub fn ty(&self, index: usize) -> Option<Type<'cx>> {
+        if index >= self.num_types() {
+            return None;
+        }
+
+        unsafe { Type::from_raw(ffi::mlirTupleTypeGetType(self.inner, index as isize)) }
+    }
+
+    /// Returns an iterator over the element types.
+    pub fn types(&self) -> Types<'cx> {
+        unsafe { Types::new(self.inner, self.num_types(), ffi::mlirTupleTypeGetType) }
+    }
+}
+
+impl<'cx> VectorType<'cx> {
+    /// Creates a vector type with the given shape and element type.
+    ///
+    /// An empty shape creates a 0-d vector, e.g. `vector<i32>`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the shape has dynamic dimensions, or if `element` is not an integer, index or
+    /// float type. See [`VectorType::try_get`].
+    pub fn get(element: Type<'cx>, shape: &[Dim]) -> VectorType<'cx> {
+        VectorType::try_get(element, shape).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a vector type with the given shape and element type.
+    ///
+    /// Returns [`Error::OutOfRange`] if a dimension does not fit in an `i64`. If the type is
+    /// otherwise invalid, the returned error holds the diagnostics emitted by MLIR.
+    pub fn try_get(element: Type<'cx>, shape: &[Dim]) -> Result<VectorType<'cx>, Error> {
+        let shape = raw_shape(shape)?;
+
+        get_checked("VectorType", element, |loc| unsafe {
+            ffi::mlirVectorTypeGetChecked(
+                loc,
+                shape.len() as isize,
+                shape.as_ptr(),
+                element.as_raw(),
+            )
+        })
+    }
+
+    /// Creates a vector type whose dimensions are scalable where `scalable` is `true`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shape` and `scalable` differ in length, or if the type is invalid. See
+    /// [`VectorType::try_scalable`].
+    pub fn scalable(element: Type<'cx>, shape: &[Dim], scalable: &[bool]) -> VectorType<'cx> {
+        VectorType::try_scalable(element, shape, scalable).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a vector type whose dimensions are scalable where `scalable` is `true`.
+    ///
+    /// Returns [`Error::InvalidArgument`] if `shape` and `scalable` differ in length, or
+    /// [`Error::OutOfRange`] if a dimension does not fit in an `i64`.
+    pub fn try_scalable(
+        element: Type<'cx>,
+        shape: &[Dim],
+        scalable: &[bool],
+    ) -> Result<VectorType<'cx>, Error> {
+        if shape.len() != scalable.len() {
+            return Err(Error::InvalidArgument(format!(
+                "{} dimensions but {} scalable flags",
+                shape.len(),
+                scalable.len(),
+            )));
+        }
+
+        let shape = raw_shape(shape)?;
+
+        get_checked("VectorType", element, |loc| unsafe {
+            ffi::mlirVectorTypeGetScalableChecked(
+                loc,
+                shape.len() as isize,
+                shape.as_ptr(),
+                scalable.as_ptr(),
+                element.as_raw(),
+            )
+        })
+    }
+
+    /// Returns `true` if any dimension of the vector is scalable.
+    pub fn is_scalable(&self) -> bool {
+        unsafe { ffi::mlirVectorTypeIsScalable(self.inner) }
+    }
+
+    /// Returns `true` if dimension `dim` is scalable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dim` is out of bounds.
+    pub fn is_dim_scalable(&self, dim: usize) -> bool {
+        let rank = self
+            .rank()
+            .expect("unexpected MLIR error: VectorType should be ranked");
+        assert!(dim < rank, "dimension {dim} out of bounds for rank {rank}");
+
+        unsafe { ffi::mlirVectorTypeIsDimScalable(self.inner, dim as isize) }
+    }
+}
+
+impl<'cx> RankedTensorType<'cx> {
+    /// Creates a ranked tensor type with the given shape, element type and optional encoding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type is invalid. See [`RankedTensorType::try_get`].
+    pub fn get(
+        element: Type<'cx>,
+        shape: &[Dim],
+        encoding: Option<Attribute<'cx>>,
+    ) -> RankedTensorType<'cx> {
+        RankedTensorType::try_get(element, shape, encoding).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a ranked tensor type with the given shape, element type and optional encoding.
+    ///
+    /// Returns [`Error::OutOfRange`] if a dimension does not fit in an `i64`. If the type is
+    /// otherwise invalid, the returned error holds the diagnostics emitted by MLIR.
+    pub fn try_get(
+        element: Type<'cx>,
+        shape: &[Dim],
+        encoding: Option<Attribute<'cx>>,
+    ) -> Result<RankedTensorType<'cx>, Error> {
+        let shape = raw_shape(shape)?;
+
+        get_checked("RankedTensorType", element, |loc| unsafe {
+            ffi::mlirRankedTensorTypeGetChecked(
+                loc,
+                shape.len() as isize,
+                shape.as_ptr(),
+                element.as_raw(),
+                encoding.map_or_else(null_attr, |attr| attr.as_raw()),
+            )
+        })
+    }
+
+    pub fn encoding(&self) -> Option<Attribute<'cx>> {
+        unsafe { Attribute::from_raw(ffi::mlirRankedTensorTypeGetEncoding(self.inner)) }
+    }
+}
+
+impl<'cx> UnrankedTensorType<'cx> {
+    /// # Panics
+    ///
+    /// Panics if `element` is not a valid tensor element type. See
+    /// [`UnrankedTensorType::try_get`].
+    pub fn get(element: Type<'cx>) -> UnrankedTensorType<'cx> {
+        UnrankedTensorType::try_get(element).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// If the type is invalid, the returned error holds the diagnostics emitted by MLIR.
+    pub fn try_get(element: Type<'cx>) -> Result<UnrankedTensorType<'cx>, Error> {
+        get_checked("UnrankedTensorType", element, |loc| unsafe {
+            ffi::mlirUnrankedTensorTypeGetChecked(loc, element.as_raw())
+        })
+    }
+}
+
+impl<'cx> MemRefType<'cx> {
+    /// Creates a memref type with the given shape and element type.
+    ///
+    /// If `layout` is `None`, the memref has the identity layout; if `memory_space` is `None`,
+    /// it uses the default memory space.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the type is invalid. See [`MemRefType::try_get`].
+    pub fn get(
+        element: Type<'cx>,
+        shape: &[Dim],
+        layout: Option<Attribute<'cx>>,
+        memory_space: Option<Attribute<'cx>>,
+    ) -> MemRefType<'cx> {
+        MemRefType::try_get(element, shape, layout, memory_space).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Creates a memref type with the given shape and element type.
+    ///
+    /// Returns [`Error::OutOfRange`] if a dimension does not fit in an `i64`. If the type is
+    /// otherwise invalid, the returned error holds the diagnostics emitted by MLIR.
+    pub fn try_get(
+        element: Type<'cx>,
+        shape: &[Dim],
+        layout: Option<Attribute<'cx>>,
+        memory_space: Option<Attribute<'cx>>,
+    ) -> Result<MemRefType<'cx>, Error> {
+        let shape = raw_shape(shape)?;
+
+        get_checked("MemRefType", element, |loc| unsafe {
+            ffi::mlirMemRefTypeGetChecked(
+                loc,
+                element.as_raw(),
+                shape.len() as isize,
+                shape.as_ptr(),
+                layout.map_or_else(null_attr, |attr| attr.as_raw()),
+                memory_space.map_or_else(null_attr, |attr| attr.as_raw()),
+            )
+        })
+    }
+
+    pub fn layout(&self) -> Attribute<'cx> {
+        unsafe {
+            Attribute::from_raw(ffi::mlirMemRefTypeGetLayout(self.inner))
+                .expect("unexpected MLIR error: MemRefType layout should be non-null")
+        }
+    }
+
+    /// Returns the memory space, or `None` if the memref uses the default memory space.
+    pub fn memory_space(&self) -> Option<Attribute<'cx>> {
+        unsafe { Attribute::from_raw(ffi::mlirMemRefTypeGetMemorySpace(self.inner)) }
+    }
+}
+
+impl<'cx> UnrankedMemRefType<'cx> {
+    /// # Panics
+    ///
+    /// Panics if the type is invalid. See [`UnrankedMemRefType::try_get`].
+    pub fn get(
+        element: Type<'cx>,
+        memory_space: Option<Attribute<'cx>>,
+    ) -> UnrankedMemRefType<'cx> {
+        UnrankedMemRefType::try_get(element, memory_space).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// If the type is invalid, the returned error holds the diagnostics emitted by MLIR.
+    pub fn try_get(
+        element: Type<'cx>,
+        memory_space: Option<Attribute<'cx>>,
+    ) -> Result<UnrankedMemRefType<'cx>, Error> {
+        get_checked("UnrankedMemRefType", element, |loc| unsafe {
+            ffi::mlirUnrankedMemRefTypeGetChecked(
+                loc,
+                element.as_raw(),
+                memory_space.map_or_else(null_attr, |attr| attr.as_raw()),
+            )
+        })
+    }
+
+    /// Returns the memory space, or `None` if the memref uses the default memory space.
+    pub fn memory_space(&self) -> Option<Attribute<'cx>> {
+        unsafe { Attribute::from_raw(ffi::mlirUnrankedMemrefGetMemorySpace(self.inner)) }
+    }
+}
+
+impl<'cx> OpaqueType<'cx> {
+    /// Creates a type belonging to an unregistered dialect, e.g. `!namespace<data>`.
+    pub fn get<'a, 'b, N, D>(cx: &'cx Context, namespace: N, data: D) -> OpaqueType<'cx>
+    where
+        N: Into<StringRef<'a>>,
+        D: Into<StringRef<'b>>,
+    {
+        OpaqueType::try_get(cx, namespace, data)
+            .expect("unexpected MLIR error: OpaqueType should be non-null")
+    }
+
+    pub fn try_get<'a, 'b, N, D>(
+        cx: &'cx Context,
+        namespace: N,
+        data: D,
+    ) -> Result<OpaqueType<'cx>, Error>
+    where
+        N: Into<StringRef<'a>>,
+        D: Into<StringRef<'b>>,
+    {
+        unsafe {
+            OpaqueType::from_raw(ffi::mlirOpaqueTypeGet(
+                cx.as_raw(),
+                namespace.into().inner,
+                data.into().inner,
+            ))
+        }
+        .ok_or_else(|| Error::null_handle("OpaqueType"))
+    }
+
+    pub fn dialect_namespace(&self) -> StringRef<'cx> {
+        unsafe { StringRef::from_raw(ffi::mlirOpaqueTypeGetDialectNamespace(self.inner)) }
+    }
+
+    pub fn data(&self) -> StringRef<'cx> {
+        unsafe { StringRef::from_raw(ffi::mlirOpaqueTypeGetData(self.inner)) }
+    }
+}
+
+impl<'cx> FunctionType<'cx> {
+    pub fn get(cx: &'cx Context, inputs: &[Type<'cx>], results: &[Type<'cx>]) -> FunctionType<'cx> {
+        FunctionType::try_get(cx, inputs, results)
+            .expect("unexpected MLIR error: FunctionType should be non-null")
+    }
+
+    pub fn try_get(
+        cx: &'cx Context,
+        inputs: &[Type<'cx>],
+        results: &[Type<'cx>],
+    ) -> Result<FunctionType<'cx>, Error> {
+        unsafe {
             let raw = ffi::mlirFunctionTypeGet(
-                cx,
+                cx.as_raw(),
                 inputs.len() as isize,
                 inputs.as_ptr() as *const ffi::MlirType,
                 results.len() as isize,
                 results.as_ptr() as *const ffi::MlirType,
             );
 
-            FunctionType::from_raw(raw).unwrap()
-        })
+            FunctionType::from_raw(raw).ok_or_else(|| Error::null_handle("FunctionType"))
+        }
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        unsafe { ffi::mlirFunctionTypeGetNumInputs(self.inner) as usize }
+    }
+
+    pub fn num_results(&self) -> usize {
+        unsafe { ffi::mlirFunctionTypeGetNumResults(self.inner) as usize }
+    }
+
+    /// Returns the input type at `index`, or `None` if `index` is out of bounds.
+    pub fn input(&self, index: usize) -> Option<Type<'cx>> {
+        if index >= self.num_inputs() {
+            return None;
+        }
+
+        unsafe { Type::from_raw(ffi::mlirFunctionTypeGetInput(self.inner, index as isize)) }
+    }
+
+    /// Returns the result type at `index`, or `None` if `index` is out of bounds.
+    pub fn result(&self, index: usize) -> Option<Type<'cx>> {
+        if index >= self.num_results() {
+            return None;
+        }
+
+        unsafe { Type::from_raw(ffi::mlirFunctionTypeGetResult(self.inner, index as isize)) }
+    }
+
+    pub fn inputs(&self) -> Types<'cx> {
+        unsafe { Types::new(self.inner, self.num_inputs(), ffi::mlirFunctionTypeGetInput) }
+    }
+
+    pub fn results(&self) -> Types<'cx> {
+        unsafe {
+            Types::new(
+                self.inner,
+                self.num_results(),
+                ffi::mlirFunctionTypeGetResult,
+            )
+        }
+    }
+}
+
+/// An iterator over the types contained in a tuple or function type.
+pub struct Types<'cx> {
+    parent: ffi::MlirType,
+    get: unsafe extern "C" fn(ffi::MlirType, isize) -> ffi::MlirType,
+    front: usize,
+    back: usize,
+    phantom: PhantomData<&'cx Context>,
+}
+
+impl<'cx> Types<'cx> {
+    /// # Safety
+    ///
+    /// `get` must return the contained type at each index less than `len`.
+    unsafe fn new(
+        parent: ffi::MlirType,
+        len: usize,
+        get: unsafe extern "C" fn(ffi::MlirType, isize) -> ffi::MlirType,
+    ) -> Types<'cx> {
+        Types {
+            parent,
+            get,
+            front: 0,
+            back: len,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'cx> Iterator for Types<'cx> {
+    type Item = Type<'cx>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        let item = unsafe { Type::from_raw((self.get)(self.parent, self.front as isize)) };
+        self.front += 1;
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Types<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        unsafe { Type::from_raw((self.get)(self.parent, self.back as isize)) }
+    }
+}
+
+impl ExactSizeIterator for Types<'_> {}
+
+impl FusedIterator for Types<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalable_vector() {
+        let cx = Context::create().unwrap();
+        let i32 = IntegerType::signless(&cx, 32).into();
+        let shape = [Dim::Static(2), Dim::Static(4)];
+
+        let vector = VectorType::try_scalable(i32, &shape, &[false, true]).unwrap();
+        assert_eq!(vector.to_string(), "vector<2x[4]xi32>");
+        assert!(matches!(
+            VectorType::try_scalable(i32, &shape, &[true]),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn zero_dimensional_vector() {
+        let cx = Context::create().unwrap();
+        let vector = VectorType::get(IntegerType::signless(&cx, 32).into(), &[]);
+
+        assert_eq!(vector.to_string(), "vector<i32>");
+        assert_eq!(vector.rank(), Some(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "MLIR returned a null VectorType\n")]
+    fn invalid_type_panics_with_diagnostics() {
+        let cx = Context::create().unwrap();
+        VectorType::get(IntegerType::signless(&cx, 32).into(), &[Dim::Dynamic]);
+    }
+
+    #[test]
+    fn shaped_type() {
+        let cx = Context::create().unwrap();
+        let f32 = F32Type::get(&cx).into();
+
+        let tensor = RankedTensorType::get(f32, &[Dim::Static(2), Dim::Dynamic], None);
+        assert_eq!(tensor.to_string(), "tensor<2x?xf32>");
+        assert_eq!(tensor.element_type(), f32);
+        assert_eq!(tensor.rank(), Some(2));
+        assert_eq!(tensor.dim(0), Some(Dim::Static(2)));
+        assert_eq!(tensor.dim(1), Some(Dim::Dynamic));
+        assert_eq!(tensor.dim(2), None);
+        assert_eq!(tensor.num_elements(), None);
+        assert!(!tensor.has_static_shape());
+
+        let shape = tensor.shape().unwrap();
+        assert_eq!(shape.to_string(), "2x?");
+        assert!(!shape.is_static());
+
+        let memref = MemRefType::get(f32, &[Dim::Static(3), Dim::Static(4)], None, None);
+        assert_eq!(memref.num_elements(), Some(12));
+        assert!(memref.has_static_shape());
+        assert_eq!(memref.shape().unwrap().rank(), 2);
+
+        let unranked = UnrankedTensorType::get(f32);
+        assert_eq!(unranked.element_type(), f32);
+        assert_eq!(unranked.rank(), None);
+        assert_eq!(unranked.shape(), None);
+        assert_eq!(unranked.dim(0), None);
+        assert_eq!(unranked.num_elements(), None);
+        assert!(!unranked.has_static_shape());
+    }
+
+    #[test]
+    fn shape_sizes() {
+        let cx = Context::create().unwrap();
+        let i8 = IntegerType::signless(&cx, 8).into();
+
+        let shape = Shape::from(&[u64::MAX, 2][..]);
+        assert_eq!(shape.num_elements(), None);
+        assert_eq!(Shape::default().num_elements(), Some(1));
+
+        let max = i64::MAX as u64;
+        assert!(RankedTensorType::try_get(i8, &[Dim::Static(max)], None).is_ok());
+        assert!(matches!(
+            RankedTensorType::try_get(i8, &[Dim::Static(max + 1)], None),
+            Err(Error::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            VectorType::try_get(i8, &[Dim::Static(u64::MAX)]),
+            Err(Error::OutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn complex_element_type() {
+        let cx = Context::create().unwrap();
+        let f32 = F32Type::get(&cx).into();
+        assert_eq!(ComplexType::try_get(f32).unwrap().element_type(), f32);
+
+        let index = IndexType::get(&cx).into();
+        assert!(matches!(
+            ComplexType::try_get(index),
+            Err(Error::TypeMismatch { .. })
+        ));
     }
 }
@@ -55,5 +55,17 @@ fn main() {
         llvm_build_dir.join("tools/mlir/include"),
     ];
 
+    // Compile the shim first so that it is linked before the static MLIR libraries it uses.
+    cc::Build::new()
+        .cpp(true)
+        .file("shim.cpp")
+        .includes(&include_dirs)
+        .flag_if_supported("-std=c++17")
+        .flag_if_supported("-fno-rtti")
+        .warnings(false)
+        .compile("mlir_sys_shim");
+    println!("cargo:rerun-if-changed=shim.h");
+    println!("cargo:rerun-if-changed=shim.cpp");
+
     setup_llvm_build(&lib_names, &include_dirs);
 }
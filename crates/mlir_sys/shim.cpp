@@ -0,0 +1,33 @@
+#include "shim.h"
+
+#include <algorithm>
+
+#include <mlir/CAPI/IR.h>
+#include <mlir/CAPI/Support.h>
+#include <mlir/IR/BuiltinAttributes.h>
+#include <mlir/IR/BuiltinOps.h>
+#include <mlir/Parser/Parser.h>
+
+using namespace mlir;
+
+// Mirrors `mlirModuleCreateParse`, which always names the source buffer "".
+MlirModule mlirRsModuleCreateParseWithName(MlirContext context, MlirStringRef module,
+                                           MlirStringRef sourceName) {
+  OwningOpRef<ModuleOp> owning = parseSourceString<ModuleOp>(
+      unwrap(module), ParserConfig(unwrap(context)), unwrap(sourceName));
+  if (!owning)
+    return MlirModule{nullptr};
+  return wrap(owning.release());
+}
+
+// The typed accessors such as `mlirDenseInt32ResourceElementsAttrGetValue` dereference the blob
+// without checking that it exists.
+bool mlirRsDenseResourceElementsAttrHasBlob(MlirAttribute attr) {
+  return llvm::cast<DenseResourceElementsAttr>(unwrap(attr)).getRawHandle().getBlob() != nullptr;
+}
+
+// The C API only exposes values which fit in 64 bits.
+void mlirRsIntegerAttrGetValueWords(MlirAttribute attr, uint64_t *words) {
+  llvm::APInt value = llvm::cast<IntegerAttr>(unwrap(attr)).getValue();
+  std::copy_n(value.getRawData(), value.getNumWords(), words);
+}
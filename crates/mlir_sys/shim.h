@@ -0,0 +1,32 @@
+// Additions to the MLIR C API which are not provided upstream.
+
+#ifndef MLIR_SYS_SHIM_H
+#define MLIR_SYS_SHIM_H
+
+#include <mlir-c/IR.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/// Parses a module from `module`, using `sourceName` as the filename in locations. As with
+/// `mlirModuleCreateParse`, the top-level operations are wrapped in an implicit module unless the
+/// source holds a single `builtin.module`. Returns a null module on failure.
+MlirModule mlirRsModuleCreateParseWithName(MlirContext context, MlirStringRef module,
+                                           MlirStringRef sourceName);
+
+/// Returns whether the resource referenced by `attr`, a `DenseResourceElementsAttr`, holds a
+/// blob. Resources which were parsed without a definition, e.g. `dense_resource<name>` outside of
+/// a file with a resource section, have none.
+bool mlirRsDenseResourceElementsAttrHasBlob(MlirAttribute attr);
+
+/// Writes the value of `attr`, an `IntegerAttr`, to `words` as little-endian 64-bit words, with
+/// any bits above the width of its type zeroed. `words` must hold at least `ceil(width / 64)`
+/// words, where index types are 64 bits wide.
+void mlirRsIntegerAttrGetValueWords(MlirAttribute attr, uint64_t *words);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // MLIR_SYS_SHIM_H
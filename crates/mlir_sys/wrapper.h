@@ -13,3 +13,4 @@
 #include <mlir-c/RegisterEverything.h>
 #include <mlir-c/Support.h>
 #include <mlir-c/Transforms.h>
+#include "shim.h"